@@ -0,0 +1,96 @@
+use std::fmt::Write;
+
+pub struct DepthMap {
+    depths: Vec<Vec<usize>>,
+}
+
+impl DepthMap {
+    pub fn new(depths: Vec<Vec<usize>>) -> Self {
+        DepthMap { depths }
+    }
+
+    pub fn width(&self) -> usize {
+        self.depths.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.depths.len()
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.depths.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.depths
+            .iter()
+            .map(|row| row.iter().sum::<usize>())
+            .sum()
+    }
+
+    /// One character per cell: '.' for untouched ground, then 1-9 and a-z for deeper levels.
+    pub fn to_heatmap(&self) -> String {
+        self.depths
+            .iter()
+            .map(|row| row.iter().map(|&d| depth_char(d)).collect::<String>() + "\n")
+            .collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.depths
+            .iter()
+            .map(|row| {
+                let line: Vec<String> = row.iter().map(|d| d.to_string()).collect();
+                line.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Plain (P2) grayscale image where the deepest cell is white and untouched ground is black.
+    pub fn to_pgm(&self) -> String {
+        let max_value = self.max_depth().clamp(1, u16::MAX as usize);
+        let mut pgm = format!("P2\n{} {}\n{}\n", self.width(), self.height(), max_value);
+
+        for row in self.depths.iter() {
+            let line: Vec<String> = row.iter().map(|&d| d.min(max_value).to_string()).collect();
+            pgm.push_str(&line.join(" "));
+            pgm.push('\n');
+        }
+
+        pgm
+    }
+
+    pub fn side_by_side(&self, other: &DepthMap) -> String {
+        let left = self.to_heatmap();
+        let right = other.to_heatmap();
+        let mut left_lines = left.lines();
+        let mut right_lines = right.lines();
+        let width = self.width();
+
+        let mut output = String::new();
+        loop {
+            match (left_lines.next(), right_lines.next()) {
+                (None, None) => break,
+                (l, r) => {
+                    let _ = writeln!(
+                        output,
+                        "{:width$}   {}",
+                        l.unwrap_or(""),
+                        r.unwrap_or(""),
+                        width = width
+                    );
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn depth_char(depth: usize) -> char {
+    match depth {
+        0 => '.',
+        1..=35 => char::from_digit(depth as u32, 36).unwrap_or('+'),
+        _ => '+',
+    }
+}
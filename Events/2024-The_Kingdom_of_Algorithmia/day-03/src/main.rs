@@ -1,3 +1,8 @@
+mod depth_map;
+
+use depth_map::DepthMap;
+use std::path::Path;
+
 fn main() {
     let input1 = include_str!("../input1");
     let input2 = include_str!("../input2");
@@ -16,20 +21,37 @@ fn main() {
         (-1, 1),
     ];
 
-    let mut grid1 = parse(input1);
-    let count1 = count(&mut grid1, &offsets4);
-    println!("Part 1: {}", count1);
+    let map1 = excavate(parse(input1), &offsets4);
+    println!("Part 1: {}", map1.total());
+
+    let map2 = excavate(parse(input2), &offsets4);
+    println!("Part 2: {}", map2.total());
+
+    let map3 = excavate(parse(input3), &offsets8);
+    println!("Part 3: {}", map3.total());
 
-    let mut grid2 = parse(input2);
-    let count2 = count(&mut grid2, &offsets4);
-    println!("Part 2: {}", count2);
+    if let Some(dir) = std::env::args().nth(1) {
+        let dir = Path::new(&dir);
+        for (name, map) in [("part1", &map1), ("part2", &map2), ("part3", &map3)] {
+            export(map, dir, name).expect("Failed to export depth map");
+        }
+
+        let map3_with_4 = excavate(parse(input3), &offsets4);
+        println!();
+        println!("Part 3 input, 4-neighbours (left) vs 8-neighbours (right):");
+        print!("{}", map3_with_4.side_by_side(&map3));
+    }
+}
 
-    let mut grid3 = parse(input3);
-    let count3 = count(&mut grid3, &offsets8);
-    println!("Part 3: {}", count3);
+fn export(map: &DepthMap, dir: &Path, name: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(format!("{}.txt", name)), map.to_heatmap())?;
+    std::fs::write(dir.join(format!("{}.csv", name)), map.to_csv())?;
+    std::fs::write(dir.join(format!("{}.pgm", name)), map.to_pgm())?;
+    Ok(())
 }
 
-fn count(grid: &mut Vec<Vec<usize>>, offsets: &[(i32, i32)]) -> usize {
+fn excavate(mut grid: Vec<Vec<usize>>, offsets: &[(i32, i32)]) -> DepthMap {
     let height = grid.len() as i32;
     let width = grid[0].len() as i32;
 
@@ -56,7 +78,7 @@ fn count(grid: &mut Vec<Vec<usize>>, offsets: &[(i32, i32)]) -> usize {
         }
     }
 
-    grid.iter().map(|row| row.iter().sum::<usize>()).sum()
+    DepthMap::new(grid)
 }
 
 fn parse(input: &str) -> Vec<Vec<usize>> {
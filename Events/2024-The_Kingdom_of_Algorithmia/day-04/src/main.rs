@@ -1,4 +1,5 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use std::ops::RangeInclusive;
 
 fn main() -> Result<()> {
     let input1 = include_str!("input1");
@@ -12,39 +13,277 @@ fn main() -> Result<()> {
     println!("Part 2. {}", s2);
 
     let s3 = count_strikes_advanced(input3).with_context(|| "While solving part 3")?;
-    println!("Part 3. {}", s3);
+    println!("Part 3. {} (target height {})", s3.strikes, s3.target);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (costs, args) = match args.as_slice() {
+        [flag, value, rest @ ..] if flag == "--costs" => (Costs::parse(value)?, rest),
+        rest => (Costs::UNIT, rest),
+    };
+
+    if let Some((mode, params)) = args.split_first() {
+        let params = params
+            .iter()
+            .map(|p| p.parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .with_context(|| "Mode parameters must be non-negative numbers")?;
+        let nails = parse(input3).with_context(|| "Parsing input file")?;
+
+        match (mode.as_str(), params.as_slice()) {
+            ("level", []) => {
+                let l = level_optimally(&nails, &costs)?;
+                println!(
+                    "Down {} up {}. {} (target height {})",
+                    costs.down, costs.up, l.strikes, l.target
+                );
+            }
+            ("range", [low, high]) => {
+                let l = level_within(&nails, &costs, *low..=*high)?;
+                println!(
+                    "Range {}..={}. {} (target height {})",
+                    low, high, l.strikes, l.target
+                );
+            }
+            ("groups", [k]) => {
+                let g = level_into_groups(&nails, &costs, *k)?;
+                println!(
+                    "At most {} heights. {} (targets {:?})",
+                    k, g.strikes, g.targets
+                );
+            }
+            ("max-adjustment", [max]) => {
+                let l = level_with_max_adjustment(&nails, &costs, *max)?;
+                println!(
+                    "Max adjustment {}. {} (target height {})",
+                    max, l.strikes, l.target
+                );
+            }
+            _ => bail!(
+                "Usage: day-04 [--costs DOWN,UP] [level | range LOW HIGH | groups K | max-adjustment MAX]"
+            ),
+        }
+    }
 
     Ok(())
 }
 
+struct Nail {
+    height: usize,
+    weight: usize,
+}
+
+/// Price of moving a nail one unit down (hammering) or one unit up (pulling).
+struct Costs {
+    down: usize,
+    up: usize,
+}
+
+impl Costs {
+    const UNIT: Costs = Costs { down: 1, up: 1 };
+
+    /// Reads `DOWN,UP`, e.g. `3,1` for hammering three times as dear as pulling.
+    fn parse(s: &str) -> Result<Costs> {
+        let (down, up) = s
+            .split_once(',')
+            .with_context(|| format!("Costs {} should be written DOWN,UP", s))?;
+        Ok(Costs {
+            down: down
+                .parse()
+                .with_context(|| "Could not parse the cost of hammering down")?,
+            up: up
+                .parse()
+                .with_context(|| "Could not parse the cost of pulling up")?,
+        })
+    }
+
+    fn moving(&self, nail: &Nail, target: usize) -> usize {
+        let per_unit = match nail.height > target {
+            true => self.down,
+            false => self.up,
+        };
+        nail.weight * per_unit * nail.height.abs_diff(target)
+    }
+}
+
+struct Levelling {
+    target: usize,
+    strikes: usize,
+}
+
+struct Grouping {
+    targets: Vec<usize>,
+    strikes: usize,
+}
+
 fn count_strikes(input: &str) -> Result<usize> {
     let nails = parse(input).with_context(|| "Parsing input file")?;
     let target: usize = nails
         .iter()
+        .map(|n| n.height)
         .min()
-        .copied()
         .with_context(|| "Taking min from empty nails list.")?;
-    Ok(nails.iter().map(|n| n.abs_diff(target)).sum())
+    Ok(nails.iter().map(|n| Costs::UNIT.moving(n, target)).sum())
 }
 
-fn count_strikes_advanced(input: &str) -> Result<usize> {
+fn count_strikes_advanced(input: &str) -> Result<Levelling> {
     let nails = parse(input).with_context(|| "Parsing input file")?;
+    level_optimally(&nails, &Costs::UNIT).with_context(|| "Finding optimal target height")
+}
+
+/// The total cost is convex in the target height, with slope `up * W(below) - down * W(above)`.
+/// It is minimised at the first nail height where the weighted quantile `down / (up + down)` is
+/// reached, i.e. the weighted median for symmetric costs.
+fn level_optimally(nails: &[Nail], costs: &Costs) -> Result<Levelling> {
+    let mut sorted: Vec<&Nail> = nails.iter().collect();
+    sorted.sort_unstable_by_key(|n| n.height);
+
+    let total_weight: usize = sorted.iter().map(|n| n.weight).sum();
+    let mut weight_at_or_below = 0;
 
-    let strikes = nails
+    let target = sorted
         .iter()
-        .map(|target| nails.iter().map(|n| n.abs_diff(*target)).sum())
+        .find(|n| {
+            weight_at_or_below += n.weight;
+            costs.up * weight_at_or_below >= costs.down * (total_weight - weight_at_or_below)
+        })
+        .or(sorted.last())
+        .map(|n| n.height)
+        .with_context(|| "Cannot level an empty list of nails.")?;
+
+    let strikes = nails.iter().map(|n| costs.moving(n, target)).sum();
+
+    Ok(Levelling { target, strikes })
+}
+
+/// Since the cost is convex, clamping the unconstrained optimum into the range is optimal.
+fn level_within(nails: &[Nail], costs: &Costs, range: RangeInclusive<usize>) -> Result<Levelling> {
+    let (low, high) = Some((*range.start(), *range.end()))
+        .filter(|(low, high)| low <= high)
+        .with_context(|| format!("Target range {:?} is empty.", range))?;
+
+    let best = level_optimally(nails, costs)?;
+    let target = best.target.clamp(low, high);
+    let strikes = nails.iter().map(|n| costs.moving(n, target)).sum();
+
+    Ok(Levelling { target, strikes })
+}
+
+/// Every nail may move at most `max` units, so the target must lie within `max` of both the
+/// lowest and the highest nail.
+fn level_with_max_adjustment(nails: &[Nail], costs: &Costs, max: usize) -> Result<Levelling> {
+    let lowest = nails
+        .iter()
+        .map(|n| n.height)
         .min()
-        .with_context(|| "Taking min number of strikes")?;
+        .with_context(|| "Cannot level an empty list of nails.")?;
+    let highest = nails.iter().map(|n| n.height).max().unwrap_or(lowest);
+
+    let range = highest.saturating_sub(max)..=lowest + max;
+    level_within(nails, costs, range).with_context(|| {
+        format!(
+            "No common target within {} of both {} and {}.",
+            max, lowest, highest
+        )
+    })
+}
+
+/// Optimal groups are contiguous runs of the sorted heights, so this is a dynamic program over
+/// the distinct heights where each group is levelled to its own weighted quantile.
+fn level_into_groups(nails: &[Nail], costs: &Costs, k: usize) -> Result<Grouping> {
+    let mut heights: Vec<(usize, usize)> = nails.iter().map(|n| (n.height, n.weight)).collect();
+    heights.sort_unstable();
+    heights.dedup_by(|next, prev| {
+        let same = next.0 == prev.0;
+        if same {
+            prev.1 += next.1;
+        }
+        same
+    });
+
+    let m = heights.len();
+    let groups = Some(k.min(m))
+        .filter(|&groups| groups > 0)
+        .with_context(|| format!("Cannot level {} nails into {} heights.", nails.len(), k))?;
+
+    let mut weights = vec![0; m + 1];
+    let mut sums = vec![0; m + 1];
+    for (i, (h, w)) in heights.iter().enumerate() {
+        weights[i + 1] = weights[i] + w;
+        sums[i + 1] = sums[i] + h * w;
+    }
+
+    // Levels heights[from..to] to a single target and returns (target, strikes).
+    let group_cost = |from: usize, to: usize| -> (usize, usize) {
+        let reaches_quantile = |t: usize| {
+            costs.up * (weights[t + 1] - weights[from])
+                >= costs.down * (weights[to] - weights[t + 1])
+        };
+        let (mut t, mut last) = (from, to - 1);
+        while t < last {
+            let mid = (t + last) / 2;
+            match reaches_quantile(mid) {
+                true => last = mid,
+                false => t = mid + 1,
+            }
+        }
+        let target = heights[t].0;
+        let (below_w, below_s) = (weights[t + 1] - weights[from], sums[t + 1] - sums[from]);
+        let (above_w, above_s) = (weights[to] - weights[t + 1], sums[to] - sums[t + 1]);
+        let strikes =
+            costs.up * (target * below_w - below_s) + costs.down * (above_s - target * above_w);
+        (target, strikes)
+    };
+
+    // best[g][j]: cheapest way to level the first j heights into g groups, and where the last
+    // group starts.
+    let mut best = vec![vec![(usize::MAX, 0); m + 1]; groups + 1];
+    best[0][0] = (0, 0);
+    for g in 1..=groups {
+        for to in g..=m {
+            for from in g - 1..to {
+                let (previous, _) = best[g - 1][from];
+                if previous == usize::MAX {
+                    continue;
+                }
+                let candidate = previous + group_cost(from, to).1;
+                if candidate < best[g][to].0 {
+                    best[g][to] = (candidate, from);
+                }
+            }
+        }
+    }
+
+    let strikes = best[groups][m].0;
+    let mut targets = Vec::with_capacity(groups);
+    let mut to = m;
+    for g in (1..=groups).rev() {
+        let from = best[g][to].1;
+        targets.push(group_cost(from, to).0);
+        to = from;
+    }
+    targets.reverse();
 
-    Ok(strikes)
+    Ok(Grouping { targets, strikes })
 }
 
-fn parse(input: &str) -> Result<Vec<usize>> {
+/// Each row holds a nail height, optionally followed by how many nails share that height.
+fn parse(input: &str) -> Result<Vec<Nail>> {
     input
         .lines()
         .map(|line| {
-            line.parse::<usize>()
-                .with_context(|| "Could not parse a row in input into a number")
+            let mut fields = line.split_whitespace();
+            let height = fields
+                .next()
+                .with_context(|| "Found an empty row in input")?
+                .parse::<usize>()
+                .with_context(|| "Could not parse a row in input into a number")?;
+            let weight = match fields.next() {
+                Some(w) => w
+                    .parse::<usize>()
+                    .with_context(|| "Could not parse the weight of a nail into a number")?,
+                None => 1,
+            };
+            Ok(Nail { height, weight })
         })
         .collect()
 }
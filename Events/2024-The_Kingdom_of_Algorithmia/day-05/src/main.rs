@@ -1,11 +1,89 @@
-use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet, VecDeque};
+use anyhow::{Context, Result, bail};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 
 fn main() -> Result<()> {
     part_one().with_context(|| "Failed during part 1.")?;
     part_two().with_context(|| "Failed during part 2.")?;
     part_three().with_context(|| "Failed during part 3.")?;
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        variation(&args).with_context(|| "Failed during variation.")?;
+    }
+
+    Ok(())
+}
+
+/// Dances the part 1 input under the rules given on the command line, e.g.
+/// `--order 3,2,1,0 --wrap same --score sum --rounds 100 --shout 2323,5`, where `--shout` asks for
+/// the round at which a number is shouted for the k-th time.
+fn variation(args: &[String]) -> Result<()> {
+    let input1 = include_str!("../input1");
+    let dance = Dance::parse(input1)?;
+
+    let mut rules = Rules::standard(dance.grid.len());
+    let mut rounds = 10;
+    let mut shout = None;
+
+    for pair in args.chunks(2) {
+        let [flag, value] = pair else {
+            bail!("Missing a value for {}", pair[0]);
+        };
+
+        match flag.as_str() {
+            "--order" => {
+                rules.order = value
+                    .split(',')
+                    .map(|c| c.parse::<usize>())
+                    .collect::<Result<_, _>>()
+                    .with_context(|| "Column order must be a comma separated list of numbers.")?
+            }
+            "--wrap" => {
+                rules.wrap = match value.as_str() {
+                    "opposite" => Wrap::OppositeSide,
+                    "same" => Wrap::SameSide,
+                    _ => bail!("Unknown wrap {}, expected opposite or same.", value),
+                }
+            }
+            "--score" => {
+                rules.score = match value.as_str() {
+                    "concat" => concat_fronts,
+                    "sum" => sum_fronts,
+                    _ => bail!("Unknown score {}, expected concat or sum.", value),
+                }
+            }
+            "--rounds" => rounds = value.parse().with_context(|| "Rounds must be a number.")?,
+            "--shout" => {
+                let (number, k) = value
+                    .split_once(',')
+                    .context("Shout must be a number and a count, e.g. 2323,5.")?;
+                shout = Some((
+                    number
+                        .parse()
+                        .with_context(|| "Shouted number must be a number.")?,
+                    k.parse().with_context(|| "Shout count must be a number.")?,
+                ));
+            }
+            _ => bail!("Unknown option {}", flag),
+        }
+    }
+
+    let mut dance = dance.with_rules(rules)?;
+    dance.advance(rounds)?;
+
+    println!("Variation. {} after round {}", dance.get_number()?, rounds);
+
+    if let Some((number, k)) = shout {
+        match dance.nth_shout(number, k)? {
+            Some(round) => println!(
+                "Variation. {} is shouted {} times by round {}",
+                number, k, round
+            ),
+            None => println!("Variation. {} is never shouted {} times", number, k),
+        }
+    }
     Ok(())
 }
 
@@ -13,9 +91,7 @@ fn part_one() -> Result<()> {
     let input1 = include_str!("../input1");
     let mut dance = Dance::parse(input1)?;
 
-    for _ in 0..10 {
-        dance.move_dancer()?;
-    }
+    dance.advance(10)?;
 
     println!("Part 1. {}", dance.get_number()?);
     Ok(())
@@ -25,142 +101,442 @@ fn part_two() -> Result<()> {
     let input2 = include_str!("../input2");
     let mut dance = Dance::parse(input2)?;
 
-    let mut counts: HashMap<usize, usize> = HashMap::new();
+    let (number, round) = dance
+        .first_to_reach(2024)?
+        .context("No number is ever shouted 2024 times.")?;
 
-    loop {
-        dance.move_dancer()?;
-        let number = dance.get_number()?;
-        let count: &mut usize = counts.entry(number).or_default();
-        *count += 1;
-
-        if *count == 2024 {
-            println!(
-                "Part 2. {} at round {}. Prod = {}",
-                number,
-                dance.get_round(),
-                number * dance.get_round(),
-            );
-            return Ok(());
-        }
-    }
+    println!(
+        "Part 2. {} at round {}. Prod = {}",
+        number,
+        round,
+        number * round,
+    );
+    Ok(())
 }
 
 fn part_three() -> Result<()> {
     let input3 = include_str!("../input3");
     let mut dance = Dance::parse(input3)?;
 
-    let mut max_number = dance.get_number()?;
-    let mut seen_dances = HashSet::from([dance.grid.clone()]);
+    let max_number = dance
+        .close_cycle()?
+        .numbers
+        .iter()
+        .max()
+        .copied()
+        .context("The dance has no rounds.")?;
+
+    println!("Part 3. Largest number: {}", max_number);
+    Ok(())
+}
+
+/// The rounds from `start` repeat with `period`.
+#[derive(Clone, Copy)]
+struct Cycle {
+    start: usize,
+    period: usize,
+}
+
+/// What a dance has shouted since round `offset`, kept while it is simulated until a state repeats.
+/// States are remembered by hash, and a match is only trusted once the earlier state has been
+/// danced again from `origin` and compared in full. Later states that only share a hash are kept
+/// in `collisions`.
+struct History {
+    offset: usize,
+    origin: Vec<VecDeque<usize>>,
+    seen: HashMap<u64, usize>,
+    collisions: HashMap<u64, Vec<usize>>,
+    numbers: Vec<usize>,
+    cycle: Option<Cycle>,
+}
+
+/// Rounds at which a number is shouted, before the cycle and within its first full period.
+#[derive(Default)]
+struct Occurrences {
+    before: Vec<usize>,
+    within: Vec<usize>,
+}
+
+impl Occurrences {
+    fn nth(&self, k: usize, period: usize) -> Result<Option<usize>> {
+        let Some(k) = k.checked_sub(1) else {
+            return Ok(None);
+        };
+
+        if k < self.before.len() {
+            return Ok(self.before.get(k).copied());
+        }
+
+        let k = k - self.before.len();
+        let per_period = self.within.len();
+        let Some(round) = self.within.get(k % per_period.max(1)) else {
+            return Ok(None);
+        };
+
+        (k / per_period)
+            .checked_mul(period)
+            .and_then(|skipped| skipped.checked_add(*round))
+            .context("Round number overflowed.")
+            .map(Some)
+    }
+}
+
+impl History {
+    fn new(offset: usize, origin: &[VecDeque<usize>]) -> Self {
+        History {
+            offset,
+            origin: origin.to_vec(),
+            seen: HashMap::new(),
+            collisions: HashMap::new(),
+            numbers: Vec::new(),
+            cycle: None,
+        }
+    }
+
+    fn record(
+        &mut self,
+        grid: &[VecDeque<usize>],
+        rules: &Rules,
+        round: usize,
+        number: usize,
+    ) -> Result<()> {
+        if self.cycle.is_some() {
+            return Ok(());
+        }
+
+        // A repeat has to land on the same move of the column order, so states are only compared
+        // at the start of each pass through it. The period found is the same, and the start of the
+        // cycle at most one pass late.
+        if !round.is_multiple_of(rules.order.len()) {
+            self.numbers.push(number);
+            return Ok(());
+        }
 
-    loop {
-        dance.move_dancer()?;
-        let number = dance.get_number()?;
-        max_number = max_number.max(number);
-        let is_new_grid = seen_dances.insert(dance.grid.clone());
+        let mut hasher = DefaultHasher::new();
+        grid.hash(&mut hasher);
+        let hash = hasher.finish();
 
-        if !is_new_grid {
-            println!("Part 3. Largest number: {}", max_number);
+        let Some(&first) = self.seen.get(&hash) else {
+            self.seen.insert(hash, round);
+            self.numbers.push(number);
             return Ok(());
+        };
+
+        let mut candidates = vec![first];
+        candidates.extend(self.collisions.get(&hash).into_iter().flatten());
+        for start in candidates {
+            if self.state_at(rules, start)? == grid {
+                self.cycle = Some(Cycle {
+                    start,
+                    period: round - start,
+                });
+                return Ok(());
+            }
+        }
+
+        self.collisions.entry(hash).or_default().push(round);
+        self.numbers.push(number);
+        Ok(())
+    }
+
+    fn state_at(&self, rules: &Rules, round: usize) -> Result<Vec<VecDeque<usize>>> {
+        let mut grid = self.origin.clone();
+        for r in self.offset + 1..=round {
+            rules.move_dancer(&mut grid, r)?;
         }
+        Ok(grid)
+    }
+
+    fn number_at(&self, round: usize, cycle: Cycle) -> usize {
+        let index = match round < cycle.start {
+            true => round - self.offset,
+            false => cycle.start - self.offset + (round - cycle.start) % cycle.period,
+        };
+        self.numbers[index]
+    }
+
+    /// Only rounds after `offset` count, since a number is shouted once a dancer has moved.
+    fn occurrences(&self, cycle: Cycle) -> HashMap<usize, Occurrences> {
+        let first = self.offset + 1;
+        let cycle_start = cycle.start.max(first);
+        let mut occurrences: HashMap<usize, Occurrences> = HashMap::new();
+
+        for round in first..cycle_start {
+            let entry = occurrences.entry(self.number_at(round, cycle)).or_default();
+            entry.before.push(round);
+        }
+
+        for round in cycle_start..cycle_start + cycle.period {
+            let entry = occurrences.entry(self.number_at(round, cycle)).or_default();
+            entry.within.push(round);
+        }
+
+        occurrences
     }
 }
 
 struct Dance {
     grid: Vec<VecDeque<usize>>,
     round: usize,
+    rules: Rules,
+    history: History,
 }
 
 impl Dance {
     fn parse(input: &str) -> Result<Self> {
-        let mut columns = Vec::new();
+        let mut columns: Vec<VecDeque<usize>> = Vec::new();
 
-        for (i, line) in input.lines().enumerate() {
+        for line in input.lines() {
             let row = line.split_whitespace().map(|n| n.parse::<usize>());
 
-            if i == 0 {
-                for n in row {
-                    columns.push(VecDeque::from([
-                        n.with_context(|| "Could not parse input as usize")?
-                    ]));
-                }
-            } else {
-                for (j, n) in row.enumerate() {
-                    columns[j].push_back(n.with_context(|| "Could not parse input as usize.")?);
+            for (j, n) in row.enumerate() {
+                if j == columns.len() {
+                    columns.push(VecDeque::new());
                 }
+                columns[j].push_back(n.with_context(|| "Could not parse input as usize.")?);
             }
         }
 
-        Ok(Dance {
-            grid: columns,
+        if columns.is_empty() {
+            bail!("The dance has no columns.");
+        }
+
+        let mut dance = Dance {
+            rules: Rules::standard(columns.len()),
             round: 0,
-        })
+            history: History::new(0, &columns),
+            grid: columns,
+        };
+        dance.record()?;
+        Ok(dance)
     }
 
-    fn print(&self) {
-        for column in self.grid.iter() {
-            for v in column.iter() {
-                print!("{} ", *v);
-            }
-            println!();
+    fn with_rules(mut self, rules: Rules) -> Result<Self> {
+        if rules.order.is_empty() {
+            bail!("The column order must visit at least one column.");
         }
-        println!();
-        println!();
+        if let Some(column) = rules.order.iter().find(|&&c| c >= self.grid.len()) {
+            bail!(
+                "Column {} in the order does not exist, the dance has {} columns.",
+                column,
+                self.grid.len()
+            );
+        }
+
+        self.rules = rules;
+        self.history = History::new(self.round, &self.grid);
+        self.record()?;
+        Ok(self)
     }
 
-    fn get_round(&self) -> usize {
-        self.round
+    fn record(&mut self) -> Result<()> {
+        if self.history.cycle.is_none() {
+            let number = self.get_number()?;
+            self.history
+                .record(&self.grid, &self.rules, self.round, number)?;
+        }
+        Ok(())
     }
 
-    fn get_number(&self) -> Result<usize> {
-        let concat_string: String = self
-            .grid
-            .iter()
-            .map(|col| {
-                col.front()
-                    .context("A column in the dance is empty.")
-                    .map(|n| n.to_string())
-            })
-            .collect::<Result<String>>()?;
+    fn step(&mut self) -> Result<()> {
+        self.move_dancer()?;
+        self.record()
+    }
 
-        concat_string
-            .parse::<usize>()
-            .with_context(|| "Failed to build a number from the dancer in front.")
+    /// Dances on until a state repeats. Every round since the history started is then known.
+    fn close_cycle(&mut self) -> Result<&History> {
+        while self.history.cycle.is_none() {
+            self.step()?;
+        }
+        Ok(&self.history)
     }
 
-    fn move_dancer(&mut self) -> Result<()> {
-        self.round += 1;
+    /// The first number to be shouted `k` times, and the round it happens. The dance stops as soon
+    /// as that is known, and only counts on from the cycle if it closes first.
+    fn first_to_reach(&mut self, k: usize) -> Result<Option<(usize, usize)>> {
+        if let Some(found) = self.count_until(k, |_| true)? {
+            return Ok(Some(found));
+        }
+
+        let cycle = self.history.cycle.context("Cycle was not found.")?;
+        let mut first = None;
+        for (number, occurrences) in self.history.occurrences(cycle) {
+            if let Some(round) = occurrences.nth(k, cycle.period)?
+                && first.is_none_or(|(_, earliest)| round < earliest)
+            {
+                first = Some((number, round));
+            }
+        }
+        Ok(first)
+    }
 
-        let columns = self.grid.len();
-        let i_from = (self.round - 1) % columns;
-        let i_to = (i_from + 1) % columns;
+    /// The round at which `number` is shouted for the `k`-th time, if it ever is.
+    fn nth_shout(&mut self, number: usize, k: usize) -> Result<Option<usize>> {
+        if let Some((_, round)) = self.count_until(k, |n| n == number)? {
+            return Ok(Some(round));
+        }
 
-        let dancer = self.grid[i_from]
-            .pop_front()
-            .context("Trid to take a dancer from a column that is empty.")?;
+        let cycle = self.history.cycle.context("Cycle was not found.")?;
+        match self.history.occurrences(cycle).get(&number) {
+            Some(occurrences) => occurrences.nth(k, cycle.period),
+            None => Ok(None),
+        }
+    }
 
-        let column_len = self.grid[i_to].len();
+    /// Counts the shouts of the numbers that `counted` accepts, dancing on until one of them is
+    /// shouted `k` times. Gives up with `None` once the cycle closes.
+    fn count_until(
+        &mut self,
+        k: usize,
+        counted: impl Fn(usize) -> bool,
+    ) -> Result<Option<(usize, usize)>> {
+        if self.history.cycle.is_some() {
+            return Ok(None);
+        }
 
-        let is_moving_down = match column_len == 1 {
-            true => dancer % 2 == 1,
-            false => ((dancer - 1) / column_len) % 2 == 0,
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        let mut count = |number: usize| {
+            let count = counts.entry(number).or_default();
+            *count += 1;
+            counted(number) && *count == k
         };
 
-        let is_at_endpoint = dancer % column_len == 0;
+        for (i, &number) in self.history.numbers.iter().enumerate().skip(1) {
+            if count(number) {
+                return Ok(Some((number, self.history.offset + i)));
+            }
+        }
 
-        let insert_index = if is_at_endpoint {
-            match is_moving_down {
-                true => column_len - 1,
-                false => 1,
+        loop {
+            self.step()?;
+            if self.history.cycle.is_some() {
+                return Ok(None);
             }
-        } else {
-            match is_moving_down {
-                true => (dancer - 1) % column_len,
-                false => column_len - (dancer % column_len) + 1,
+
+            let number = *self.history.numbers.last().context("No rounds recorded.")?;
+            if count(number) {
+                return Ok(Some((number, self.round)));
             }
+        }
+    }
+
+    /// Moves `rounds` ahead. Once the states repeat, whole periods are skipped, so any round
+    /// number is reachable.
+    fn advance(&mut self, rounds: usize) -> Result<()> {
+        let target = self
+            .round
+            .checked_add(rounds)
+            .context("Round number overflowed.")?;
+
+        while self.round < target {
+            if let Some(cycle) = self.history.cycle {
+                let remaining = target - self.round;
+                self.round += remaining - remaining % cycle.period;
+                for _ in 0..remaining % cycle.period {
+                    self.move_dancer()?;
+                }
+                break;
+            }
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    fn get_number(&self) -> Result<usize> {
+        (self.rules.score)(&self.grid)
+    }
+
+    fn move_dancer(&mut self) -> Result<()> {
+        self.round += 1;
+        self.rules.move_dancer(&mut self.grid, self.round)
+    }
+}
+
+/// Where a dancer continues once the claps run past the end of the column.
+#[derive(Clone, Copy)]
+enum Wrap {
+    /// Around the bottom and back up the other side, the rule from the story.
+    OppositeSide,
+    /// Back to the top of the same side.
+    SameSide,
+}
+
+impl Wrap {
+    fn insert_index(&self, dancer: usize, column_len: usize) -> Result<usize> {
+        let clap = dancer
+            .checked_sub(1)
+            .context("A dancer with number 0 never claps.")?;
+
+        if column_len == 0 {
+            return Ok(0);
+        }
+
+        let index = match self {
+            Wrap::OppositeSide => match clap % (2 * column_len) {
+                down if down < column_len => down,
+                up => 2 * column_len - up,
+            },
+            Wrap::SameSide => clap % column_len,
         };
 
-        self.grid[i_to].insert(insert_index, dancer);
+        Ok(index)
+    }
+}
+
+type Score = fn(&[VecDeque<usize>]) -> Result<usize>;
+
+#[derive(Clone)]
+struct Rules {
+    order: Vec<usize>,
+    wrap: Wrap,
+    score: Score,
+}
+
+impl Rules {
+    fn standard(columns: usize) -> Self {
+        Rules {
+            order: (0..columns).collect(),
+            wrap: Wrap::OppositeSide,
+            score: concat_fronts,
+        }
+    }
+
+    /// Moves the dancer whose turn it is in `round`, counting rounds from 1.
+    fn move_dancer(&self, grid: &mut [VecDeque<usize>], round: usize) -> Result<()> {
+        let moves = self.order.len();
+        let i_from = self.order[(round - 1) % moves];
+        let i_to = self.order[round % moves];
+
+        let dancer = grid[i_from]
+            .pop_front()
+            .context("Trid to take a dancer from a column that is empty.")?;
+
+        let insert_index = self.wrap.insert_index(dancer, grid[i_to].len())?;
+
+        grid[i_to].insert(insert_index, dancer);
 
         Ok(())
     }
 }
+
+fn concat_fronts(grid: &[VecDeque<usize>]) -> Result<usize> {
+    let concat_string: String = grid
+        .iter()
+        .map(|col| {
+            col.front()
+                .context("A column in the dance is empty.")
+                .map(|n| n.to_string())
+        })
+        .collect::<Result<String>>()?;
+
+    concat_string
+        .parse::<usize>()
+        .with_context(|| "Failed to build a number from the dancer in front.")
+}
+
+fn sum_fronts(grid: &[VecDeque<usize>]) -> Result<usize> {
+    grid.iter()
+        .map(|col| col.front().context("A column in the dance is empty."))
+        .sum()
+}
@@ -1,110 +1,71 @@
-use anyhow::{Context, Result};
-use std::collections::{HashMap, VecDeque};
-
-fn main() {
-    part_one();
-    part_two();
-    part_three();
-}
-
-fn part_one() {
-    let input1 = include_str!("../input1");
-    let roots = parse(input1);
-
-    let mut queue = VecDeque::from([("".to_owned(), "RR".to_owned(), 1)]);
-    let mut fruit_path_by_distances = HashMap::<usize, Vec<String>>::new();
-
-    while let Some((path, root, dist)) = queue.pop_front() {
-        if let Some(destinations) = roots.get(&root) {
-            for destination in destinations {
-                match destination.as_str() {
-                    "@" => {
-                        fruit_path_by_distances
-                            .entry(dist)
-                            .or_default()
-                            .push(format!("{}{}@", path, &root[0..1]));
-                    }
-                    _ => {
-                        queue.push_back((
-                            format!("{}{}", path, &root[0..1]),
-                            destination.clone(),
-                            dist + 1,
-                        ));
-                    }
-                }
-            }
-        }
+mod tree;
+
+use anyhow::{Context, Result, bail};
+use tree::{Tree, length_histogram};
+
+fn main() -> Result<()> {
+    let inputs = [
+        (include_str!("../input1"), &[][..]),
+        (include_str!("../input2"), &["BUG", "ANT"][..]),
+        (include_str!("../input3"), &["BUG", "ANT"][..]),
+    ];
+
+    for (part, (input, pests)) in inputs.iter().enumerate() {
+        let path =
+            get_path(input, pests).with_context(|| format!("Failed during part {}.", part + 1))?;
+        println!("Part {}. Path: {}", part + 1, path);
     }
 
-    if let Some(paths) = fruit_path_by_distances
-        .values()
-        .find(|paths| paths.len() == 1)
-    {
-        println!("Part 1. Path: {}", paths[0]);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, part] = args.as_slice() {
+        let (input, pests) = part
+            .parse::<usize>()
+            .ok()
+            .and_then(|p| inputs.get(p.wrapping_sub(1)))
+            .with_context(|| format!("There is no part {}.", part))?;
+        let tree = load(input, pests)?;
+
+        match command.as_str() {
+            "report" => report(&tree)?,
+            "dot" => print!("{}", tree.to_dot(&tree.unique_length_path()?)),
+            _ => bail!("Usage: day-06 [report|dot PART]"),
+        }
     }
-}
 
-fn part_two() {
-    let input2 = include_str!("../input2");
-    let path = get_path(input2).expect("Should return path 2.");
-    println!("Part 2. Path: {}", path);
+    Ok(())
 }
 
-fn part_three() {
-    let input3 = include_str!("../input3");
-    let path = get_path(input3).expect("Should return path 3.");
-    println!("Part 3. Path: {}", path);
-}
-
-fn get_path(input: &str) -> Result<String> {
-    let roots = parse(input);
+fn load(input: &str, pests: &[&str]) -> Result<Tree> {
+    let tree = Tree::parse(input)?.with_pests(pests);
 
-    let mut queue = VecDeque::from([("".to_owned(), "RR".to_owned(), 1)]);
-    let mut fruit_path_by_distances = HashMap::<usize, Vec<String>>::new();
-
-    while let Some((previous_path, branching_root, dist)) = queue.pop_front() {
-        if let Some(destinations) = roots.get(&branching_root) {
-            destinations
-                .iter()
-                .for_each(|destination| match destination.as_str() {
-                    "BUG" | "ANT" => (),
-                    "@" => {
-                        fruit_path_by_distances
-                            .entry(dist)
-                            .or_default()
-                            .push(format!("{}{}@", previous_path, &branching_root[0..1]));
-                    }
-                    _ => {
-                        queue.push_back((
-                            format!("{}{}", previous_path, &branching_root[0..1]),
-                            destination.clone(),
-                            dist + 1,
-                        ));
-                    }
-                });
-        }
+    let diagnostics = tree.diagnose();
+    if !diagnostics.is_empty() && !diagnostics.is_fatal() {
+        eprint!("{}", diagnostics);
     }
 
-    let unique_lengh_path = fruit_path_by_distances
-        .values()
-        .find(|paths| paths.len() == 1)
-        .context("There is no path with a unique path-length.")?[0]
-        .clone();
-
-    Ok(unique_lengh_path)
+    Ok(tree)
 }
 
-fn parse(input: &str) -> HashMap<String, Vec<String>> {
-    let mut roots = HashMap::new();
+fn get_path(input: &str, pests: &[&str]) -> Result<String> {
+    let tree = load(input, pests)?;
+    let path = tree.unique_length_path()?;
+
+    let letters: String = path.iter().map(|&b| &tree.name(b)[0..1]).collect();
+    Ok(format!("{}@", letters))
+}
 
-    for line in input.lines() {
-        let (root, branches) = line
-            .split_once(':')
-            .expect("Root and branches should be separated by a ':'");
+fn report(tree: &Tree) -> Result<()> {
+    let by_length = tree.paths_by_length()?;
 
-        let branches = branches.split(',').map(|s| s.to_string()).collect();
-        roots.insert(root.to_string(), branches);
+    println!();
+    for (length, paths) in by_length.iter() {
+        println!("Length {} ({} paths)", length, paths.len());
+        for path in paths {
+            println!("    {} -> @", tree.path_names(path).join(" -> "));
+        }
     }
 
-    roots
+    println!();
+    print!("{}", length_histogram(&by_length, 40));
+    Ok(())
 }
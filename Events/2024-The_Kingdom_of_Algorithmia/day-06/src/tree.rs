@@ -0,0 +1,338 @@
+use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write};
+
+const ROOT: &str = "RR";
+const FRUIT: &str = "@";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Child {
+    Branch(usize),
+    Fruit,
+}
+
+pub struct Tree {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    children: Vec<Vec<Child>>,
+    defined: Vec<bool>,
+    duplicates: Vec<String>,
+    pests: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct Diagnostics {
+    pub missing_root: bool,
+    pub cycles: Vec<Vec<String>>,
+    pub unreachable: Vec<String>,
+    pub undefined: Vec<String>,
+    pub duplicates: Vec<String>,
+}
+
+impl Diagnostics {
+    /// Problems that make the fruit paths meaningless, as opposed to mere oddities.
+    pub fn is_fatal(&self) -> bool {
+        self.missing_root || !self.cycles.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.missing_root
+            && self.cycles.is_empty()
+            && self.unreachable.is_empty()
+            && self.undefined.is_empty()
+            && self.duplicates.is_empty()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.missing_root {
+            writeln!(f, "The tree has no root branch {}.", ROOT)?;
+        }
+        for cycle in self.cycles.iter() {
+            writeln!(f, "Cycle: {}", cycle.join(" -> "))?;
+        }
+        if !self.unreachable.is_empty() {
+            writeln!(
+                f,
+                "Unreachable from {}: {}",
+                ROOT,
+                self.unreachable.join(", ")
+            )?;
+        }
+        if !self.undefined.is_empty() {
+            writeln!(f, "Never defined: {}", self.undefined.join(", "))?;
+        }
+        if !self.duplicates.is_empty() {
+            writeln!(f, "Defined more than once: {}", self.duplicates.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Tree {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut tree = Tree {
+            names: Vec::new(),
+            index: HashMap::new(),
+            children: Vec::new(),
+            defined: Vec::new(),
+            duplicates: Vec::new(),
+            pests: HashSet::new(),
+        };
+
+        for (row, line) in input.lines().enumerate() {
+            let (root, branches) = line.split_once(':').with_context(|| {
+                format!(
+                    "Root and branches should be separated by a ':' on line {}",
+                    row + 1
+                )
+            })?;
+
+            if root.is_empty() || root == FRUIT {
+                bail!("Invalid branch name '{}' on line {}", root, row + 1);
+            }
+
+            let children = branches
+                .split(',')
+                .map(|name| match name {
+                    "" => bail!("Empty branch name on line {}", row + 1),
+                    FRUIT => Ok(Child::Fruit),
+                    _ => Ok(Child::Branch(tree.node(name))),
+                })
+                .collect::<Result<Vec<Child>>>()?;
+
+            let node = tree.node(root);
+            if tree.defined[node] {
+                tree.duplicates.push(root.to_string());
+            }
+            tree.defined[node] = true;
+            tree.children[node].extend(children);
+        }
+
+        Ok(tree)
+    }
+
+    /// Branches with these names are never followed.
+    pub fn with_pests(mut self, pests: &[&str]) -> Self {
+        self.pests = pests.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    fn node(&mut self, name: &str) -> usize {
+        if let Some(&node) = self.index.get(name) {
+            return node;
+        }
+
+        let node = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), node);
+        self.children.push(Vec::new());
+        self.defined.push(false);
+        node
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.names[node]
+    }
+
+    pub fn root(&self) -> Option<usize> {
+        self.index.get(ROOT).copied()
+    }
+
+    fn is_pest(&self, node: usize) -> bool {
+        self.pests.contains(&self.names[node])
+    }
+
+    /// Children that are followed, i.e. everything except pests.
+    pub fn children(&self, node: usize) -> impl Iterator<Item = Child> + '_ {
+        self.children[node]
+            .iter()
+            .copied()
+            .filter(|child| !matches!(child, Child::Branch(b) if self.is_pest(*b)))
+    }
+
+    pub fn diagnose(&self) -> Diagnostics {
+        let mut diagnostics = Diagnostics {
+            duplicates: self.duplicates.clone(),
+            ..Default::default()
+        };
+
+        let Some(root) = self.root() else {
+            diagnostics.missing_root = true;
+            return diagnostics;
+        };
+
+        #[derive(Clone, Copy, PartialEq)]
+        enum Visit {
+            New,
+            OnStack,
+            Done,
+        }
+
+        let mut visits = vec![Visit::New; self.names.len()];
+        let mut stack: Vec<(usize, Vec<Child>)> = vec![(root, self.children(root).collect())];
+        visits[root] = Visit::OnStack;
+
+        while let Some((node, pending)) = stack.last_mut() {
+            let node = *node;
+            match pending.pop() {
+                Some(Child::Fruit) => (),
+                Some(Child::Branch(next)) => match visits[next] {
+                    Visit::New => {
+                        visits[next] = Visit::OnStack;
+                        stack.push((next, self.children(next).collect()));
+                    }
+                    Visit::OnStack => {
+                        let from = stack.iter().position(|(n, _)| *n == next).unwrap_or(0);
+                        let mut cycle: Vec<String> = stack[from..]
+                            .iter()
+                            .map(|(n, _)| self.names[*n].clone())
+                            .collect();
+                        cycle.push(self.names[next].clone());
+                        diagnostics.cycles.push(cycle);
+                    }
+                    Visit::Done => (),
+                },
+                None => {
+                    visits[node] = Visit::Done;
+                    stack.pop();
+                }
+            }
+        }
+
+        for (node, name) in self.names.iter().enumerate() {
+            if self.is_pest(node) {
+                continue;
+            }
+            if visits[node] == Visit::New && self.defined[node] {
+                diagnostics.unreachable.push(name.clone());
+            }
+            if visits[node] == Visit::Done && !self.defined[node] {
+                diagnostics.undefined.push(name.clone());
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Every path from the root to a fruit, as the branches passed on the way.
+    pub fn fruit_paths(&self) -> Result<Vec<Vec<usize>>> {
+        let diagnostics = self.diagnose();
+        if diagnostics.is_fatal() {
+            bail!("The tree cannot be traversed.\n{}", diagnostics);
+        }
+
+        let root = self.root().context("The tree has no root.")?;
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![root]];
+
+        while let Some(path) = stack.pop() {
+            let Some(&last) = path.last() else {
+                continue;
+            };
+
+            for child in self.children(last) {
+                match child {
+                    Child::Fruit => paths.push(path.clone()),
+                    Child::Branch(next) => {
+                        let mut longer = path.clone();
+                        longer.push(next);
+                        stack.push(longer);
+                    }
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+
+    pub fn paths_by_length(&self) -> Result<BTreeMap<usize, Vec<Vec<usize>>>> {
+        let mut by_length: BTreeMap<usize, Vec<Vec<usize>>> = BTreeMap::new();
+        for path in self.fruit_paths()? {
+            by_length.entry(path.len()).or_default().push(path);
+        }
+        for paths in by_length.values_mut() {
+            paths.sort_by(|a, b| self.path_names(a).cmp(&self.path_names(b)));
+        }
+        Ok(by_length)
+    }
+
+    pub fn unique_length_path(&self) -> Result<Vec<usize>> {
+        self.paths_by_length()?
+            .into_values()
+            .find(|paths| paths.len() == 1)
+            .and_then(|mut paths| paths.pop())
+            .context("There is no path with a unique path-length.")
+    }
+
+    pub fn path_names(&self, path: &[usize]) -> Vec<&str> {
+        path.iter().map(|&b| self.name(b)).collect()
+    }
+
+    /// Graphviz rendering of the whole tree, with the given root-to-fruit path drawn in red and
+    /// pests greyed out.
+    pub fn to_dot(&self, highlight: &[usize]) -> String {
+        let highlighted: HashSet<(usize, Option<usize>)> = highlight
+            .windows(2)
+            .map(|w| (w[0], Some(w[1])))
+            .chain(highlight.last().map(|&last| (last, None)))
+            .collect();
+
+        let mut dot = String::from("digraph tree {\n    rankdir=BT;\n");
+
+        for (node, name) in self.names.iter().enumerate() {
+            let style = match (self.is_pest(node), highlight.contains(&node)) {
+                (true, _) => ", color=grey, fontcolor=grey, style=dashed",
+                (false, true) => ", color=red, penwidth=2",
+                (false, false) => "",
+            };
+            let _ = writeln!(dot, "    n{} [label=\"{}\"{}];", node, name, style);
+        }
+
+        for (node, children) in self.children.iter().enumerate() {
+            for (i, child) in children.iter().enumerate() {
+                let (target, edge) = match child {
+                    Child::Branch(next) => (format!("n{}", next), (node, Some(*next))),
+                    Child::Fruit => {
+                        let fruit = format!("f{}_{}", node, i);
+                        let _ = writeln!(dot, "    {} [label=\"@\", shape=circle];", fruit);
+                        (fruit, (node, None))
+                    }
+                };
+                let style = match highlighted.contains(&edge) {
+                    true => " [color=red, penwidth=2]",
+                    false => "",
+                };
+                let _ = writeln!(dot, "    n{} -> {}{};", node, target, style);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// One bar per path length, scaled so the longest bar is at most `width` characters.
+pub fn length_histogram(by_length: &BTreeMap<usize, Vec<Vec<usize>>>, width: usize) -> String {
+    let most = by_length
+        .values()
+        .map(|p| p.len())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut histogram = String::new();
+
+    for (length, paths) in by_length.iter() {
+        let bar = (paths.len() * width).div_ceil(most);
+        let _ = writeln!(
+            histogram,
+            "{:>4} | {} {}",
+            length,
+            "#".repeat(bar),
+            paths.len()
+        );
+    }
+
+    histogram
+}
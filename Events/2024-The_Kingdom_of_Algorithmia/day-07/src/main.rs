@@ -1,7 +1,11 @@
+mod planner;
+mod report;
+mod track;
+
 use anyhow::{Context, Result, bail};
-use glam::IVec2;
-use itertools::{Itertools, Position};
-use std::collections::HashMap;
+use planner::{DeviceCounts, search_plans};
+use report::RaceReport;
+use track::parse_track;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum Device {
@@ -26,153 +30,138 @@ struct Race {
 }
 
 impl Race {
-    fn score(&self) -> i32 {
+    /// Total essence gathered after each lap, with the plan running on continuously across laps.
+    fn lap_totals(&self, track: &[Segment], laps: usize) -> Vec<i64> {
         let mut acc = 10;
-
-        self.devices
-            .iter()
-            .map(|device| {
-                acc = std::cmp::Ord::max(
-                    0,
-                    acc + match device {
-                        Device::Plus => 1,
-                        Device::Minus => -1,
-                        Device::Equal => 0,
-                    },
-                );
-                acc
+        let mut total = 0;
+        let mut devices = self.devices.iter().cycle();
+
+        (0..laps)
+            .map(|_| {
+                for (seg, dev) in track.iter().zip(&mut devices) {
+                    acc = std::cmp::Ord::max(0, acc + essence_change(*seg, *dev));
+                    total += acc;
+                }
+                total
             })
-            .sum()
+            .collect()
     }
 
     fn score_on_track(&self, track: &[Segment], repeats: usize) -> i64 {
-        let mut acc = 10;
-        let full_track = track.iter().cycle().take(track.len() * repeats);
+        self.lap_totals(track, repeats).last().copied().unwrap_or(0)
+    }
+}
 
-        std::iter::zip(full_track, self.devices.iter().cycle())
-            .map(|(seg, dev)| {
-                acc = std::cmp::Ord::max(
-                    0,
-                    acc + match seg {
-                        Segment::Plus => 1,
-                        Segment::Minus => -1,
-                        Segment::Equal | Segment::S => match dev {
-                            Device::Plus => 1,
-                            Device::Minus => -1,
-                            Device::Equal => 0,
-                        },
-                    },
-                );
-                acc
-            })
-            .sum()
+fn essence_change(segment: Segment, device: Device) -> i64 {
+    match segment {
+        Segment::Plus => 1,
+        Segment::Minus => -1,
+        Segment::Equal | Segment::S => match device {
+            Device::Plus => 1,
+            Device::Minus => -1,
+            Device::Equal => 0,
+        },
     }
 }
 
 fn main() -> Result<()> {
-    part_one().context("Failed to run part one")?;
-    part_two().context("Failed to run part two")?;
-    part_three().context("Failed to run part three")?;
+    let report1 = part_one().context("Failed to run part one")?;
+    let report2 = part_two().context("Failed to run part two")?;
+    let report3 = part_three().context("Failed to run part three")?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [format, part] = args.as_slice() {
+        let report = match part.as_str() {
+            "1" => &report1,
+            "2" => &report2,
+            "3" => &report3,
+            _ => bail!("There is no part {}", part),
+        };
+        match format.as_str() {
+            "csv" => print!("{}", report.to_csv()),
+            "json" => print!("{}", report.to_json()),
+            _ => bail!("Usage: day-07 [csv|json PART]"),
+        }
+    }
+
     Ok(())
 }
 
-fn part_one() -> Result<()> {
+fn part_one() -> Result<RaceReport> {
     let input1 = include_str!("../input1.txt");
     let races = parse(input1).context("Failed to parse input")?;
-    let results = races
-        .iter()
-        .map(|race| (race.id.clone(), race.score()))
-        .collect::<Vec<_>>();
 
-    let order = results
+    // Without a track every segment lets the device decide, so each step counts as a lap, and the
+    // race is one pass over the plans.
+    let steps = races
         .iter()
-        .sorted_by_key(|(_, score)| *score)
-        .rev()
-        .map(|x| x.0.as_str())
-        .collect::<String>();
+        .map(|race| race.devices.len())
+        .max()
+        .unwrap_or(0);
+    let report = RaceReport::new(&races, &[Segment::Equal], steps);
 
-    println!("Part 1: {}", order);
-    Ok(())
+    println!("Part 1: {}", report.order());
+    Ok(report)
 }
 
-fn part_two() -> Result<()> {
+fn part_two() -> Result<RaceReport> {
     let races_input = include_str!("../input2.txt");
     let track_input = include_str!("../track2.txt");
 
     let races = parse(races_input).context("Failed to parse input")?;
-    let track = parse_rect_track(track_input);
+    let track = parse_track(track_input)?;
 
-    let results: Vec<(&str, i64)> = races
-        .iter()
-        .map(|race| (race.id.as_str(), race.score_on_track(&track, 10)))
-        .collect();
-
-    let order = results
-        .iter()
-        .sorted_by_key(|(_, score)| *score)
-        .rev()
-        .map(|res| res.0)
-        .collect::<String>();
+    let report = RaceReport::new(&races, &track, 10);
 
-    println!("Part 2. {}", order);
-    Ok(())
+    println!("Part 2. {}", report.order());
+    Ok(report)
 }
 
-fn part_three() -> Result<()> {
+fn part_three() -> Result<RaceReport> {
     let races_input = include_str!("../input3.txt");
     let track_input = include_str!("../track3.txt");
 
     let races = parse(races_input).context("Failed to parse input")?;
-    let track = parse_curvy_track(track_input);
-    let score_to_beat = races[0].score_on_track(&track, 2024);
-
-    let all_plans = get_valid_action_plans();
-
-    let count = all_plans
-        .into_iter()
-        .map(|plan| {
-            let race = Race {
-                id: "".to_owned(),
-                devices: plan,
-            };
-
-            race.score_on_track(&track, 2024)
-        })
-        .filter(|score| *score > score_to_beat)
-        .count();
-
-    println!("Part 3. {}", count);
-
-    Ok(())
+    let track = parse_track(track_input)?;
+    let rival = races.first().context("There is no rival plan to beat")?;
+    let score_to_beat = rival.score_on_track(&track, 2024);
+
+    let counts = DeviceCounts {
+        plus: 5,
+        minus: 3,
+        equal: 3,
+    };
+    let search = search_plans(&track, &counts, 2024, score_to_beat)
+        .context("There are no action plans to try")?;
+
+    println!(
+        "Part 3. {} (best plan {} scores {})",
+        search.winning_plans,
+        plan_symbols(&search.best_plan),
+        search.best_score
+    );
+
+    let contenders = [
+        Race {
+            id: rival.id.clone(),
+            devices: rival.devices.clone(),
+        },
+        Race {
+            id: "best".to_owned(),
+            devices: search.best_plan,
+        },
+    ];
+    Ok(RaceReport::new(&contenders, &track, 2024))
 }
 
-fn get_valid_action_plans() -> Vec<Plan> {
-    fn generate(current: Plan, p: usize, m: usize, e: usize) -> Vec<Plan> {
-        let mut plans: Vec<Plan> = vec![];
-
-        if (p, m, e) == (0, 0, 0) {
-            return vec![current];
-        }
-
-        if p > 0 {
-            let ncurrent = [current.clone(), vec![Device::Plus]].concat();
-            plans.extend(generate(ncurrent, p - 1, m, e));
-        }
-
-        if m > 0 {
-            let ncurrent = [current.clone(), vec![Device::Minus]].concat();
-            plans.extend(generate(ncurrent, p, m - 1, e));
-        }
-
-        if e > 0 {
-            let ncurrent = [current.clone(), vec![Device::Equal]].concat();
-            plans.extend(generate(ncurrent, p, m, e - 1));
-        }
-
-        plans
-    }
-
-    generate(vec![], 5, 3, 3)
+fn plan_symbols(plan: &[Device]) -> String {
+    plan.iter()
+        .map(|device| match device {
+            Device::Plus => '+',
+            Device::Minus => '-',
+            Device::Equal => '=',
+        })
+        .collect()
 }
 
 fn parse(input: &str) -> Result<Vec<Race>> {
@@ -199,94 +188,3 @@ fn parse(input: &str) -> Result<Vec<Race>> {
         })
         .collect()
 }
-
-fn parse_rect_track(input: &str) -> Vec<Segment> {
-    let mut track = vec![];
-    let mut left_col = vec![];
-    let mut right_col = vec![];
-
-    for (pos, line) in input.lines().with_position() {
-        match pos {
-            Position::First => {
-                track.extend(line.chars().skip(1));
-                left_col.push(line.chars().next().unwrap());
-            }
-            Position::Middle => {
-                let mut chars = line.chars().filter(|c| !c.is_whitespace());
-                left_col.push(chars.next().unwrap());
-                right_col.push(chars.next().unwrap());
-            }
-            Position::Last => {
-                track.extend(right_col.clone());
-                track.extend(line.chars().rev());
-                track.extend(left_col.clone().into_iter().rev());
-            }
-            Position::Only => panic!("Track should not be a single row!"),
-        }
-    }
-
-    track
-        .into_iter()
-        .map(|c| match c {
-            '+' => Segment::Plus,
-            '-' => Segment::Minus,
-            '=' => Segment::Equal,
-            'S' => Segment::S,
-            _ => panic!("Unexpected char in track!"),
-        })
-        .collect()
-}
-
-fn parse_curvy_track(input: &str) -> Vec<Segment> {
-    let mut grid = HashMap::<IVec2, Segment>::new();
-
-    input.lines().enumerate().for_each(|(y, row)| {
-        row.chars().enumerate().for_each(|(x, c)| {
-            if !c.is_whitespace() {
-                let seg = match c {
-                    '+' => Segment::Plus,
-                    '-' => Segment::Minus,
-                    '=' => Segment::Equal,
-                    'S' => Segment::S,
-                    _ => panic!("Unexpected char in track!"),
-                };
-
-                grid.insert(
-                    IVec2 {
-                        x: x as i32,
-                        y: y as i32,
-                    },
-                    seg,
-                );
-            }
-        });
-    });
-
-    let mut track = Vec::<Segment>::new();
-
-    let mut cur = IVec2::new(0, 0);
-    let mut dir = IVec2::X;
-
-    loop {
-        let next_dirs = [dir, dir.rotate(IVec2::NEG_Y), dir.rotate(IVec2::Y)];
-
-        if let Some((d, Some(v))) = next_dirs
-            .map(|d| (d, grid.get(&(cur + d))))
-            .into_iter()
-            .find(|(_, v)| v.is_some())
-        {
-            track.push(*v);
-
-            cur = cur + d;
-            dir = d;
-
-            if *v == Segment::S {
-                break;
-            }
-        } else {
-            panic!("Could not find next step along the track!");
-        }
-    }
-
-    track
-}
@@ -0,0 +1,145 @@
+use crate::{Device, Plan, Segment, essence_change};
+use std::collections::HashMap;
+
+pub struct DeviceCounts {
+    pub plus: usize,
+    pub minus: usize,
+    pub equal: usize,
+}
+
+pub struct PlanSearch {
+    pub best_score: i64,
+    pub best_plan: Plan,
+    pub winning_plans: usize,
+}
+
+/// Total essence a plan gathers over `laps` laps of the track, without stepping through every lap.
+pub fn fast_score(track: &[Segment], plan: &[Device], laps: usize) -> i64 {
+    if track.is_empty() || plan.is_empty() {
+        return 0;
+    }
+
+    let period = lcm(track.len(), plan.len());
+    let deltas: Vec<i64> = (0..period)
+        .map(|i| essence_change(track[i % track.len()], plan[i % plan.len()]))
+        .collect();
+
+    let mut prefix = 0;
+    let mut min_prefix = 0;
+    let mut prefix_total = 0;
+    for delta in deltas.iter() {
+        prefix += delta;
+        min_prefix = min_prefix.min(prefix);
+        prefix_total += prefix;
+    }
+    let net = prefix;
+    let length = period as i64;
+
+    let steps = track.len() * laps;
+    let (periods, rest) = (steps / period, steps % period);
+
+    let mut acc: i64 = 10;
+    let mut total: i64 = 0;
+    let mut seen: Option<HashMap<i64, (usize, i64)>> = Some(HashMap::new());
+    let mut k = 0;
+
+    while k < periods {
+        if net >= 0 && acc + min_prefix >= 0 {
+            let n = (periods - k) as i64;
+            total += n * (length * acc + prefix_total) + length * net * n * (n - 1) / 2;
+            acc += net * n;
+            break;
+        }
+
+        if let Some(starts) = seen.as_mut() {
+            if let Some(&(k0, total0)) = starts.get(&acc) {
+                let cycle_len = k - k0;
+                let cycles = (periods - k) / cycle_len;
+                total += cycles as i64 * (total - total0);
+                k += cycles * cycle_len;
+                seen = None;
+                continue;
+            }
+            starts.insert(acc, (k, total));
+        }
+
+        let (sum, end) = run(&deltas, acc);
+        total += sum;
+        acc = end;
+        k += 1;
+    }
+
+    total + run(&deltas[..rest], acc).0
+}
+
+fn run(deltas: &[i64], mut acc: i64) -> (i64, i64) {
+    let mut total = 0;
+    for delta in deltas {
+        acc = (acc + delta).max(0);
+        total += acc;
+    }
+    (total, acc)
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+/// Scores every distinct ordering of the given devices, without building them one by one.
+pub fn search_plans(
+    track: &[Segment],
+    counts: &DeviceCounts,
+    laps: usize,
+    score_to_beat: i64,
+) -> Option<PlanSearch> {
+    fn visit(plan: &mut Plan, remaining: [usize; 3], on_plan: &mut impl FnMut(&Plan)) {
+        if remaining == [0, 0, 0] {
+            on_plan(plan);
+            return;
+        }
+
+        for (i, device) in [Device::Plus, Device::Minus, Device::Equal]
+            .into_iter()
+            .enumerate()
+        {
+            if remaining[i] > 0 {
+                let mut fewer = remaining;
+                fewer[i] -= 1;
+                plan.push(device);
+                visit(plan, fewer, on_plan);
+                plan.pop();
+            }
+        }
+    }
+
+    let mut search: Option<PlanSearch> = None;
+    let remaining = [counts.plus, counts.minus, counts.equal];
+
+    visit(&mut Vec::new(), remaining, &mut |plan| {
+        let score = fast_score(track, plan, laps);
+        let beats = (score > score_to_beat) as usize;
+
+        match search.as_mut() {
+            Some(s) => {
+                s.winning_plans += beats;
+                if score > s.best_score {
+                    s.best_score = score;
+                    s.best_plan = plan.clone();
+                }
+            }
+            None => {
+                search = Some(PlanSearch {
+                    best_score: score,
+                    best_plan: plan.clone(),
+                    winning_plans: beats,
+                })
+            }
+        }
+    });
+
+    search.filter(|s| !s.best_plan.is_empty())
+}
@@ -0,0 +1,127 @@
+use crate::{Race, Segment};
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+pub struct Standing {
+    pub id: String,
+    /// Essence gathered so far, after each completed lap.
+    pub cumulative: Vec<i64>,
+}
+
+impl Standing {
+    pub fn total(&self) -> i64 {
+        self.cumulative.last().copied().unwrap_or(0)
+    }
+}
+
+/// Standings sorted from winner to last place.
+pub struct RaceReport {
+    pub laps: usize,
+    pub standings: Vec<Standing>,
+}
+
+/// Ranks two chariots by total essence, then by who was ahead at the last lap where they
+/// differed, and finally by id so the order is always well defined.
+fn rank(a: &Standing, b: &Standing) -> Ordering {
+    b.total()
+        .cmp(&a.total())
+        .then_with(|| {
+            std::iter::zip(a.cumulative.iter(), b.cumulative.iter())
+                .rev()
+                .find(|(x, y)| x != y)
+                .map_or(Ordering::Equal, |(x, y)| y.cmp(x))
+        })
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+impl RaceReport {
+    pub fn new(races: &[Race], track: &[Segment], laps: usize) -> Self {
+        let mut standings: Vec<Standing> = races
+            .iter()
+            .map(|race| Standing {
+                id: race.id.clone(),
+                cumulative: race.lap_totals(track, laps),
+            })
+            .collect();
+
+        standings.sort_by(rank);
+
+        RaceReport { laps, standings }
+    }
+
+    pub fn order(&self) -> String {
+        self.standings.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    /// Place of every chariot after each lap, indexed like `standings`.
+    fn positions(&self) -> Vec<Vec<usize>> {
+        let mut positions = vec![Vec::with_capacity(self.laps); self.standings.len()];
+
+        for lap in 0..self.laps {
+            let mut order: Vec<usize> = (0..self.standings.len()).collect();
+            order.sort_by(|&a, &b| {
+                let (a, b) = (&self.standings[a], &self.standings[b]);
+                b.cumulative[lap]
+                    .cmp(&a.cumulative[lap])
+                    .then_with(|| a.id.cmp(&b.id))
+            });
+            for (place, i) in order.into_iter().enumerate() {
+                positions[i].push(place + 1);
+            }
+        }
+
+        positions
+    }
+
+    /// One row per chariot and lap, with the essence gained on that lap and the place held after it.
+    pub fn to_csv(&self) -> String {
+        let positions = self.positions();
+        let mut csv = String::from("rank,id,lap,gained,cumulative,position\n");
+
+        for (rank, standing) in self.standings.iter().enumerate() {
+            let mut previous = 0;
+            for (lap, &cumulative) in standing.cumulative.iter().enumerate() {
+                let _ = writeln!(
+                    csv,
+                    "{},{},{},{},{},{}",
+                    rank + 1,
+                    standing.id,
+                    lap + 1,
+                    cumulative - previous,
+                    cumulative,
+                    positions[rank][lap]
+                );
+                previous = cumulative;
+            }
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let positions = self.positions();
+        let mut json = format!("{{\n  \"laps\": {},\n  \"standings\": [\n", self.laps);
+
+        for (rank, standing) in self.standings.iter().enumerate() {
+            let cumulative: Vec<String> =
+                standing.cumulative.iter().map(|c| c.to_string()).collect();
+            let places: Vec<String> = positions[rank].iter().map(|p| p.to_string()).collect();
+            let _ = write!(
+                json,
+                "    {{\"rank\": {}, \"id\": \"{}\", \"total\": {}, \"cumulative\": [{}], \"positions\": [{}]}}",
+                rank + 1,
+                standing.id.replace('\\', "\\\\").replace('"', "\\\""),
+                standing.total(),
+                cumulative.join(", "),
+                places.join(", ")
+            );
+            json.push_str(match rank + 1 == self.standings.len() {
+                true => "\n",
+                false => ",\n",
+            });
+        }
+
+        json.push_str("  ]\n}\n");
+        json
+    }
+}
@@ -0,0 +1,126 @@
+use crate::Segment;
+use anyhow::{Context, Result, bail};
+use glam::IVec2;
+use std::collections::HashMap;
+
+struct Lap {
+    segments: Vec<Segment>,
+    cells: Vec<IVec2>,
+}
+
+impl Lap {
+    /// Twice the signed area enclosed by the lap, positive when it runs clockwise on screen.
+    fn winding(&self) -> i64 {
+        self.cells
+            .iter()
+            .zip(self.cells.iter().cycle().skip(1))
+            .map(|(a, b)| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+            .sum()
+    }
+}
+
+/// Traces the closed loop through `S`, in the clockwise direction, as the segments visited
+/// starting right after `S` and ending on it.
+pub fn parse_track(input: &str) -> Result<Vec<Segment>> {
+    let mut grid = HashMap::<IVec2, Segment>::new();
+
+    for (y, row) in input.lines().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let pos = IVec2::new(x as i32, y as i32);
+            let seg = match c {
+                '+' => Segment::Plus,
+                '-' => Segment::Minus,
+                '=' => Segment::Equal,
+                'S' => Segment::S,
+                _ if c.is_whitespace() => continue,
+                _ => bail!("Unexpected char '{}' in track at ({}, {})", c, pos.x, pos.y),
+            };
+            grid.insert(pos, seg);
+        }
+    }
+
+    let starts: Vec<IVec2> = grid
+        .iter()
+        .filter(|(_, seg)| **seg == Segment::S)
+        .map(|(pos, _)| *pos)
+        .collect();
+
+    let start = match starts.as_slice() {
+        [start] => *start,
+        [] => bail!("The track has no start 'S'"),
+        _ => bail!("The track has {} starts 'S', expected one", starts.len()),
+    };
+
+    let mut laps = Vec::new();
+    let mut first_error = None;
+
+    for dir in [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y] {
+        if !grid.contains_key(&(start + dir)) {
+            continue;
+        }
+        match trace(&grid, start, dir) {
+            Ok(lap) => laps.push(lap),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    if let Some(err) = first_error.filter(|_| laps.is_empty()) {
+        return Err(err);
+    }
+
+    let mut clockwise = laps.into_iter().filter(|lap| lap.winding() > 0);
+
+    let lap = clockwise.next().with_context(|| {
+        format!(
+            "No closed loop runs through S at ({}, {})",
+            start.x, start.y
+        )
+    })?;
+
+    if clockwise.next().is_some() {
+        bail!(
+            "More than one loop runs clockwise through S at ({}, {})",
+            start.x,
+            start.y
+        );
+    }
+
+    Ok(lap.segments)
+}
+
+/// Follows the track from `start` heading `dir`, going straight whenever possible and otherwise
+/// taking the only available turn, until it returns to `start`.
+fn trace(grid: &HashMap<IVec2, Segment>, start: IVec2, mut dir: IVec2) -> Result<Lap> {
+    let mut cur = start + dir;
+    let mut lap = Lap {
+        segments: vec![],
+        cells: vec![start],
+    };
+
+    while cur != start {
+        if lap.cells.len() > grid.len() {
+            bail!("The track never returns to S at ({}, {})", start.x, start.y);
+        }
+
+        lap.segments.push(grid[&cur]);
+        lap.cells.push(cur);
+
+        let left = IVec2::new(dir.y, -dir.x);
+        let right = IVec2::new(-dir.y, dir.x);
+
+        dir = match [dir, left, right].map(|d| grid.contains_key(&(cur + d))) {
+            [true, _, _] => dir,
+            [false, true, false] => left,
+            [false, false, true] => right,
+            [false, true, true] => bail!("The track branches at ({}, {})", cur.x, cur.y),
+            [false, false, false] => bail!("The track dead-ends at ({}, {})", cur.x, cur.y),
+        };
+
+        cur += dir;
+    }
+
+    lap.segments.push(Segment::S);
+    Ok(lap)
+}
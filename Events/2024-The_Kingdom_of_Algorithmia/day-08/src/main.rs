@@ -1,92 +1,119 @@
-fn main() {
-    part_one();
-    part_two();
-    part_three();
-}
+mod temple;
 
-fn part_one() {
-    // s(n) = n^2
-    // n^2 > T
-    // n = roof(sqrt(T))
+use temple::{Temple, build_hollow, build_layered, build_pyramid};
 
-    let input1 = include_str!("../input1");
-
-    let available_blocks = input1.parse::<usize>().expect("Should be an integer");
-    let layers = (available_blocks as f64).sqrt().ceil() as usize;
-    let base_width = 2 * layers - 1;
-    let blocks_needed = layers.pow(2);
-    let additional_blocks = blocks_needed - available_blocks;
-    let answer = additional_blocks * base_width;
-
-    println!("Part 1. {}", answer);
+struct Settings {
+    acolytes: usize,
+    marble_supply: usize,
+    high_priest_acolytes: usize,
+    platinum_supply: usize,
+    show: bool,
 }
 
-fn part_two() {
-    let input2 = include_str!("../input2");
-
-    let number_priests = input2.parse::<usize>().expect("Should be an integer");
-    let number_accolytes = 1111;
-    let priests_marble_supply = 2024_00_00;
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            acolytes: 1111,
+            marble_supply: 20_240_000,
+            high_priest_acolytes: 10,
+            platinum_supply: 202_400_000,
+            show: false,
+        }
+    }
+}
 
-    let mut current_layer_thickness = 1;
-    let mut blocks_used = 1;
-    let mut temple_width = 1;
+impl Settings {
+    fn from_args() -> Self {
+        let mut settings = Settings::default();
+        let mut args = std::env::args().skip(1);
+
+        while let Some(flag) = args.next() {
+            let field = match flag.as_str() {
+                "--show" => {
+                    settings.show = true;
+                    continue;
+                }
+                "--acolytes" => &mut settings.acolytes,
+                "--marble" => &mut settings.marble_supply,
+                "--high-acolytes" => &mut settings.high_priest_acolytes,
+                "--platinum" => &mut settings.platinum_supply,
+                _ => panic!(
+                    "Unknown option {}. Expected --acolytes, --marble, --high-acolytes, --platinum or --show",
+                    flag
+                ),
+            };
+            *field = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .expect("Option should be followed by an integer");
+        }
 
-    while blocks_used < priests_marble_supply {
-        current_layer_thickness =
-            (current_layer_thickness * number_priests).rem_euclid(number_accolytes);
+        if settings.acolytes < 2 || settings.high_priest_acolytes < 2 {
+            panic!("Temples need at least 2 acolytes, or no layer ever grows");
+        }
 
-        temple_width += 2;
-        blocks_used += temple_width * current_layer_thickness;
+        settings
     }
+}
 
-    let additional_blocks_needed = blocks_used - priests_marble_supply;
-    let answer = additional_blocks_needed * temple_width;
+fn main() {
+    let settings = Settings::from_args();
 
-    println!("Part 2. {}", answer);
+    part_one(&settings);
+    part_two(&settings);
+    part_three(&settings);
 }
 
-fn part_three() {
-    let input3 = include_str!("../input3");
-
-    let number_high_priests = input3.parse::<usize>().expect("Should be an integer");
-    let number_high_priest_accolytes = 10;
-    let priests_platinum_supply = 2024_00_000;
+fn part_one(settings: &Settings) {
+    let input1 = include_str!("../input1");
 
-    let mut columns: Vec<usize> = vec![1];
-    let mut current_layer_thickness = 1;
-    let mut blocks_needed = 1;
+    let available_blocks = input1.parse::<usize>().expect("Should be an integer");
+    let temple = build_pyramid(available_blocks);
+    let answer = temple.shortfall(available_blocks) * temple.width();
 
-    columns.reserve_exact(100_000);
+    println!("Part 1. {}", answer);
+    show(settings, &temple);
+}
 
-    while blocks_needed < priests_platinum_supply {
-        current_layer_thickness = (current_layer_thickness * number_high_priests)
-            .rem_euclid(number_high_priest_accolytes)
-            + number_high_priest_accolytes;
+fn part_two(settings: &Settings) {
+    let input2 = include_str!("../input2");
 
-        for c in columns.iter_mut() {
-            *c += current_layer_thickness;
+    let number_priests = input2.parse::<usize>().expect("Should be an integer");
+    match build_layered(number_priests, settings.acolytes, settings.marble_supply) {
+        Ok(temple) => {
+            let answer = temple.shortfall(settings.marble_supply) * temple.width();
+            println!("Part 2. {}", answer);
+            show(settings, &temple);
         }
-        columns.push(current_layer_thickness);
-
-        let base_width = 2 * columns.len() - 1;
-
-        let blocks_all_layers = columns[0] + 2 * columns.iter().skip(1).sum::<usize>();
+        Err(e) => println!("Part 2. Could not build the temple: {}", e),
+    }
+}
 
-        let mut blocks_to_remove = (base_width * number_high_priests * columns[0])
-            .rem_euclid(number_high_priest_accolytes);
+fn part_three(settings: &Settings) {
+    let input3 = include_str!("../input3");
 
-        if columns.len() > 2 {
-            for height in columns[1..columns.len() - 1].iter() {
-                blocks_to_remove += 2
-                    * (base_width * number_high_priests * height)
-                        .rem_euclid(number_high_priest_accolytes);
-            }
+    let number_high_priests = input3.parse::<usize>().expect("Should be an integer");
+    match build_hollow(
+        number_high_priests,
+        settings.high_priest_acolytes,
+        settings.platinum_supply,
+    ) {
+        Ok(temple) => {
+            println!("Part 3. {}", temple.shortfall(settings.platinum_supply));
+            show(settings, &temple);
         }
+        Err(e) => println!("Part 3. Could not build the temple: {}", e),
+    }
+}
 
-        blocks_needed = blocks_all_layers - blocks_to_remove;
+fn show(settings: &Settings, temple: &Temple) {
+    if !settings.show {
+        return;
     }
 
-    let additional_blocks_needed = blocks_needed - priests_platinum_supply;
-    println!("Part 3. {}", additional_blocks_needed);
+    println!("    Width:   {}", temple.width());
+    println!("    Blocks:  {}", temple.blocks);
+    println!("    Layers:  {:?}", temple.layers);
+    println!("    Columns: {:?}", temple.columns);
+    println!("    Removed: {:?}", temple.removed);
 }
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum BuildError {
+    NoAcolytes,
+    /// The layer with this index would add no blocks, so the supply can never be used up.
+    Stalled(usize),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::NoAcolytes => write!(f, "there are no acolytes to share out the blocks"),
+            BuildError::Stalled(layer) => {
+                write!(
+                    f,
+                    "layer {} adds no blocks, so the temple never grows",
+                    layer
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// A finished temple, described from the top layer down and from the centre column outwards.
+pub struct Temple {
+    pub layers: Vec<usize>,
+    pub columns: Vec<usize>,
+    pub removed: Vec<usize>,
+    pub blocks: usize,
+}
+
+impl Temple {
+    fn from_layers(layers: Vec<usize>, removed: Vec<usize>) -> Self {
+        let mut columns = layers.clone();
+        for i in (0..columns.len().saturating_sub(1)).rev() {
+            columns[i] += columns[i + 1];
+        }
+
+        let stacked =
+            columns.first().copied().unwrap_or(0) + 2 * columns.iter().skip(1).sum::<usize>();
+        let hollowed =
+            removed.first().copied().unwrap_or(0) + 2 * removed.iter().skip(1).sum::<usize>();
+
+        Temple {
+            layers,
+            columns,
+            removed,
+            blocks: stacked - hollowed,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (2 * self.layers.len()).saturating_sub(1)
+    }
+
+    pub fn shortfall(&self, supply: usize) -> usize {
+        self.blocks.saturating_sub(supply)
+    }
+}
+
+/// Every layer is one block thick, so `n` layers use `n^2` blocks.
+pub fn build_pyramid(supply: usize) -> Temple {
+    let layers = (supply as f64).sqrt().ceil().max(1.0) as usize;
+    Temple::from_layers(vec![1; layers], vec![0; layers])
+}
+
+/// Each layer is `priests` times thicker than the one above, modulo the number of acolytes.
+pub fn build_layered(priests: usize, acolytes: usize, supply: usize) -> Result<Temple, BuildError> {
+    if acolytes == 0 {
+        return Err(BuildError::NoAcolytes);
+    }
+
+    let mut layers = vec![1];
+    let mut thickness = 1;
+    let mut blocks_used = 1;
+    let mut temple_width = 1;
+
+    while blocks_used < supply {
+        thickness = (thickness * priests).rem_euclid(acolytes);
+        if thickness == 0 {
+            return Err(BuildError::Stalled(layers.len()));
+        }
+
+        temple_width += 2;
+        blocks_used += temple_width * thickness;
+        layers.push(thickness);
+    }
+
+    let removed = vec![0; layers.len()];
+    Ok(Temple::from_layers(layers, removed))
+}
+
+/// Layers grow like in `build_layered` but by at least `acolytes` blocks, and every column except
+/// the outermost ones is hollowed out by `width * priests * height` modulo the acolytes.
+pub fn build_hollow(priests: usize, acolytes: usize, supply: usize) -> Result<Temple, BuildError> {
+    if acolytes == 0 {
+        return Err(BuildError::NoAcolytes);
+    }
+
+    let mut builder = HollowBuilder::new(priests, acolytes);
+    let mut layers = vec![1];
+
+    while !builder.reaches(supply) {
+        layers.push(builder.add_layer());
+    }
+
+    let temple = Temple::from_layers(layers, Vec::new());
+    let factor = builder.factor();
+    let inner = temple.columns.len() - 1;
+    let removed = temple
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, height)| match i < inner {
+            true => mul_mod(factor, *height, acolytes),
+            false => 0,
+        })
+        .collect();
+
+    Ok(Temple::from_layers(temple.layers, removed))
+}
+
+/// Running totals of the hollow temple, updated as each layer is added.
+pub struct HollowBuilder {
+    priests: usize,
+    acolytes: usize,
+    thickness: usize,
+    columns: usize,
+    centre: usize,
+    others: usize,
+    outermost: usize,
+    offset: usize,
+    inner_by_residue: HashMap<usize, usize>,
+}
+
+impl HollowBuilder {
+    pub fn new(priests: usize, acolytes: usize) -> Self {
+        HollowBuilder {
+            priests,
+            acolytes,
+            thickness: 1,
+            columns: 1,
+            centre: 1,
+            others: 0,
+            outermost: 1,
+            offset: 0,
+            inner_by_residue: HashMap::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        2 * self.columns - 1
+    }
+
+    /// `width * priests` modulo the acolytes, shared by every column of the current temple.
+    fn factor(&self) -> usize {
+        mul_mod(self.width(), self.priests, self.acolytes)
+    }
+
+    pub fn add_layer(&mut self) -> usize {
+        let a = self.acolytes;
+        self.thickness = mul_mod(self.thickness, self.priests, a) + a;
+        let t = self.thickness;
+
+        self.offset = (self.offset + t) % a;
+        if self.columns > 1 {
+            let height = self.outermost + t;
+            let residue = (height % a + a - self.offset) % a;
+            *self.inner_by_residue.entry(residue).or_insert(0) += 1;
+        }
+
+        self.others += (self.columns - 1) * t + t;
+        self.centre += t;
+        self.outermost = t;
+        self.columns += 1;
+
+        t
+    }
+
+    /// Blocks before any are hollowed out.
+    fn stacked(&self) -> usize {
+        self.centre + 2 * self.others
+    }
+
+    /// The most blocks the inner columns can lose, at most one less than the acolytes each.
+    fn removal_bound(&self) -> usize {
+        (2 * self.columns).saturating_sub(3) * (self.acolytes - 1)
+    }
+
+    /// Whether the temple uses at least `supply` blocks.
+    pub fn reaches(&self, supply: usize) -> bool {
+        let stacked = self.stacked();
+        if stacked < supply {
+            false
+        } else if stacked - self.removal_bound().min(stacked) >= supply {
+            true
+        } else {
+            self.blocks() >= supply
+        }
+    }
+
+    pub fn blocks(&self) -> usize {
+        if self.columns == 1 {
+            return self.centre;
+        }
+
+        let a = self.acolytes;
+        let factor = self.factor();
+
+        let inner: usize = self
+            .inner_by_residue
+            .iter()
+            .map(|(&r, &count)| count * mul_mod(factor, r + self.offset, a))
+            .sum();
+        let removed = mul_mod(factor, self.centre, a) + 2 * inner;
+
+        self.stacked() - removed
+    }
+}
+
+fn mul_mod(x: usize, y: usize, m: usize) -> usize {
+    ((x as u128 * y as u128) % m as u128) as usize
+}
@@ -1,7 +1,91 @@
+const PART_THREE_STAMPS: [usize; 18] = [
+    1, 3, 5, 10, 15, 16, 20, 24, 25, 30, 37, 38, 49, 50, 74, 75, 100, 101,
+];
+
+struct SplitRule {
+    balls: usize,
+    max_difference: usize,
+}
+
+const PART_THREE_SPLIT: SplitRule = SplitRule {
+    balls: 2,
+    max_difference: 100,
+};
+
 fn main() {
     part_one();
     part_two();
     part_three();
+
+    let mut rule = PART_THREE_SPLIT;
+    let mut requested: Vec<usize> = Vec::new();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| -> usize {
+            args.next()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(|| panic!("{} should be followed by an integer", name))
+        };
+        match arg.as_str() {
+            "--balls" => rule.balls = value("--balls"),
+            "--max-difference" => rule.max_difference = value("--max-difference"),
+            _ => requested.push(arg.parse().expect("Brightness should be an integer")),
+        }
+    }
+
+    if !requested.is_empty() {
+        explain(&requested, &rule);
+    }
+}
+
+/// Shows every optimal way to stamp the requested brightnesses with the part 3 stamps, and the
+/// best split into balls under the given rule.
+fn explain(brightnesses: &[usize], rule: &SplitRule) {
+    let top = *brightnesses.iter().max().unwrap();
+    let table = LookUp::new(&PART_THREE_STAMPS, top);
+
+    for &brightness in brightnesses {
+        match table.stamps(brightness) {
+            Some(stamps) => {
+                let combinations = table.optimal_combinations(brightness);
+                println!(
+                    "{}: {} beetles in {} ways, e.g. {}",
+                    brightness,
+                    stamps.len(),
+                    combinations.len(),
+                    describe(&stamps)
+                );
+                for combination in combinations {
+                    println!("    {}", describe(&combination));
+                }
+            }
+            None => println!("{}: unreachable", brightness),
+        }
+
+        match table.split(brightness, rule) {
+            Some(split) => println!(
+                "    Split into {:?} with {} beetles",
+                split.balls, split.beetles
+            ),
+            None => println!(
+                "    Cannot split into {} balls at most {} apart",
+                rule.balls, rule.max_difference
+            ),
+        }
+    }
+}
+
+/// Groups a sorted list of stamps, e.g. `101x3 + 5 + 1x2`.
+fn describe(stamps: &[usize]) -> String {
+    stamps
+        .chunk_by(|a, b| a == b)
+        .map(|run| match run.len() {
+            1 => run[0].to_string(),
+            n => format!("{}x{}", run[0], n),
+        })
+        .collect::<Vec<String>>()
+        .join(" + ")
 }
 
 fn part_one() {
@@ -11,7 +95,11 @@ fn part_one() {
     let top = *brightnesses.iter().max().unwrap();
 
     let table = LookUp::new(&stamps, top);
-    let beetles: usize = brightnesses.iter().map(|&b| table.beetles(b)).sum();
+    let beetles: usize = brightnesses
+        .iter()
+        .map(|&b| table.beetles(b))
+        .sum::<Option<usize>>()
+        .expect("Every brightness should be reachable");
     println!("Part 1. Beetles: {}", beetles);
 }
 
@@ -22,57 +110,213 @@ fn part_two() {
     let top = *brightnesses.iter().max().unwrap();
 
     let table = LookUp::new(&stamps, top);
-    let beetles: usize = brightnesses.iter().map(|&b| table.beetles(b)).sum();
+    let beetles: usize = brightnesses
+        .iter()
+        .map(|&b| table.beetles(b))
+        .sum::<Option<usize>>()
+        .expect("Every brightness should be reachable");
     println!("Part 2. Beetles: {}", beetles);
 }
 
 fn part_three() {
-    let stamps = [
-        1, 3, 5, 10, 15, 16, 20, 24, 25, 30, 37, 38, 49, 50, 74, 75, 100, 101,
-    ];
     let input = include_str!("../input3.txt");
     let brightnesses = parse(input);
     let top = *brightnesses.iter().max().unwrap();
-    let table = LookUp::new(&stamps, top);
+    let table = LookUp::new(&PART_THREE_STAMPS, top);
 
-    let beetles: usize = brightnesses.iter().map(|&b| table.dual_beetles(b)).sum();
+    let beetles: usize = brightnesses
+        .iter()
+        .map(|&b| table.split(b, &PART_THREE_SPLIT).map(|split| split.beetles))
+        .sum::<Option<usize>>()
+        .expect("Every brightness should be reachable");
 
     println!("Part 3. Beetles: {}", beetles);
 }
 
 struct LookUp {
-    table: Vec<usize>,
+    stamps: Vec<usize>,
+    table: Vec<Option<usize>>,
+    last_stamp: Vec<Option<usize>>,
 }
 
 impl LookUp {
     fn new(stamps: &[usize], highest: usize) -> Self {
-        let mut table: Vec<usize> = vec![highest; highest + 1];
+        let mut stamps: Vec<usize> = stamps.iter().copied().filter(|&s| s > 0).collect();
+        stamps.sort_unstable_by(|a, b| b.cmp(a));
+        stamps.dedup();
 
-        table[0] = 0;
+        let mut table: Vec<Option<usize>> = vec![None; highest + 1];
+        let mut last_stamp: Vec<Option<usize>> = vec![None; highest + 1];
 
-        for &s in stamps {
+        table[0] = Some(0);
+
+        for &s in stamps.iter() {
             for i in s..=highest {
-                table[i] = table[i].min(1 + table[i - s]);
+                if let Some(rest) = table[i - s]
+                    && table[i].is_none_or(|current| rest + 1 < current)
+                {
+                    table[i] = Some(rest + 1);
+                    last_stamp[i] = Some(s);
+                }
             }
         }
 
-        LookUp { table }
+        LookUp {
+            stamps,
+            table,
+            last_stamp,
+        }
     }
 
-    fn beetles(&self, brightness: usize) -> usize {
-        self.table[brightness]
+    fn beetles(&self, brightness: usize) -> Option<usize> {
+        self.table.get(brightness).copied().flatten()
     }
 
-    fn dual_beetles(&self, brightness: usize) -> usize {
-        let mut min_beetles = usize::MAX;
-        let middle = brightness / 2;
+    /// One optimal set of stamps, following the stored back-pointers.
+    fn stamps(&self, brightness: usize) -> Option<Vec<usize>> {
+        let mut stamps = Vec::with_capacity(self.beetles(brightness)?);
+        let mut remaining = brightness;
 
-        for i in middle..middle + 50 {
-            min_beetles = min_beetles.min(self.table[i] + self.table[brightness - i]);
+        while remaining > 0 {
+            let stamp = self.last_stamp[remaining]?;
+            stamps.push(stamp);
+            remaining -= stamp;
         }
 
-        min_beetles
+        stamps.sort_unstable_by(|a, b| b.cmp(a));
+        Some(stamps)
     }
+
+    /// Every distinct multiset of stamps using the minimal number of beetles, largest stamp first.
+    fn optimal_combinations(&self, brightness: usize) -> Vec<Vec<usize>> {
+        fn collect(
+            lookup: &LookUp,
+            remaining: usize,
+            largest: usize,
+            current: &mut Vec<usize>,
+            combinations: &mut Vec<Vec<usize>>,
+        ) {
+            if remaining == 0 {
+                combinations.push(current.clone());
+                return;
+            }
+
+            let Some(needed) = lookup.beetles(remaining) else {
+                return;
+            };
+
+            for &s in lookup
+                .stamps
+                .iter()
+                .filter(|&&s| s <= largest && s <= remaining)
+            {
+                if lookup.beetles(remaining - s) == Some(needed - 1) {
+                    current.push(s);
+                    collect(lookup, remaining - s, s, current, combinations);
+                    current.pop();
+                }
+            }
+        }
+
+        let mut combinations = Vec::new();
+        if self.beetles(brightness).is_some() {
+            collect(
+                self,
+                brightness,
+                usize::MAX,
+                &mut Vec::new(),
+                &mut combinations,
+            );
+        }
+        combinations
+    }
+
+    /// Splits the brightness into `rule.balls` non-empty balls, no two differing by more than
+    /// `rule.max_difference`, using as few beetles as possible.
+    ///
+    /// Sorted, any valid split starts with its smallest ball `s` and has every ball within
+    /// `s..=s + max_difference`, which forces `balls * s <= brightness <= balls * (s + max_difference)`.
+    /// Every such `s` is tried, and for each every non-decreasing completion within that window, so
+    /// no valid split is missed. Branches that cannot reach the sum or beat the best so far are cut.
+    fn split(&self, brightness: usize, rule: &SplitRule) -> Option<Split> {
+        let balls = rule.balls;
+        if balls == 0 {
+            return None;
+        }
+
+        let lowest = brightness
+            .saturating_sub(balls * rule.max_difference)
+            .div_ceil(balls)
+            .max(1);
+
+        let mut search = SplitSearch {
+            look_up: self,
+            high: 0,
+            current: Vec::with_capacity(balls),
+            best: None,
+        };
+
+        for smallest in lowest..=brightness / balls {
+            let Some(beetles) = self.beetles(smallest) else {
+                continue;
+            };
+            search.high = smallest + rule.max_difference;
+            search.current.push(smallest);
+            search.complete(brightness - smallest, balls - 1, smallest, beetles);
+            search.current.pop();
+        }
+
+        search.best
+    }
+}
+
+/// The depth-first search behind `LookUp::split`, with the balls chosen so far and the best split
+/// found. Every ball of the split being built has to stay at or below `high`.
+struct SplitSearch<'a> {
+    look_up: &'a LookUp,
+    high: usize,
+    current: Vec<usize>,
+    best: Option<Split>,
+}
+
+impl SplitSearch<'_> {
+    /// Adds `left` more balls of at least `low` each, summing to `remaining`.
+    fn complete(&mut self, remaining: usize, left: usize, low: usize, beetles: usize) {
+        if self.best.as_ref().is_some_and(|b| beetles >= b.beetles) {
+            return;
+        }
+
+        if left == 0 {
+            if remaining == 0 {
+                self.best = Some(Split {
+                    balls: self.current.clone(),
+                    beetles,
+                });
+            }
+            return;
+        }
+
+        for ball in low..=self.high.min(remaining) {
+            if left * ball > remaining {
+                break;
+            }
+            if remaining - ball > (left - 1) * self.high {
+                continue;
+            }
+            let Some(more) = self.look_up.beetles(ball) else {
+                continue;
+            };
+
+            self.current.push(ball);
+            self.complete(remaining - ball, left - 1, ball, beetles + more);
+            self.current.pop();
+        }
+    }
+}
+
+struct Split {
+    balls: Vec<usize>,
+    beetles: usize,
 }
 
 fn parse(input: &str) -> Vec<usize> {
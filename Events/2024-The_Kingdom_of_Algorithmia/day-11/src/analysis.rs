@@ -0,0 +1,267 @@
+use crate::Rule;
+use crate::growth::Transitions;
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Growth {
+    Extinct,
+    /// Grows like `days^degree`, where degree 0 means the population stays bounded.
+    Polynomial(usize),
+    Exponential,
+}
+
+impl fmt::Display for Growth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Growth::Extinct => write!(f, "extinct"),
+            Growth::Polynomial(0) => write!(f, "bounded"),
+            Growth::Polynomial(degree) => write!(f, "polynomial (degree {})", degree),
+            Growth::Exponential => write!(f, "exponential"),
+        }
+    }
+}
+
+pub struct Analysis<'a> {
+    transitions: Transitions<'a>,
+    /// Produced by some rule but with no rule of their own, so they die after a day.
+    pub undefined: Vec<&'a str>,
+    /// Having several rules, whose outputs are all added together.
+    pub duplicates: Vec<&'a str>,
+    /// Never produced by any rule, so they only exist as starters.
+    pub unreachable: Vec<&'a str>,
+    /// Strongly connected components, listed with sinks first.
+    pub components: Vec<Vec<&'a str>>,
+    pub growth: Vec<Growth>,
+}
+
+/// Growth follows from the strongly connected components. A component without a cycle passes
+/// termites on and empties. A component that is one plain cycle (each member turning into exactly
+/// one other member) keeps a constant population, and a chain of `k` such cycles adds `k - 1` to
+/// the polynomial degree. Any richer cycle has spectral radius above one and grows exponentially.
+pub fn analyse<'a>(rules: &[Rule<'a>]) -> Analysis<'a> {
+    let transitions = Transitions::from_rules(rules);
+    let names = transitions.categories();
+    let n = names.len();
+
+    let mut defined = HashSet::new();
+    let mut duplicates = Vec::new();
+    for rule in rules {
+        if !defined.insert(rule.input) && !duplicates.contains(&rule.input) {
+            duplicates.push(rule.input);
+        }
+    }
+
+    let produced: HashSet<&str> = rules
+        .iter()
+        .flat_map(|r| r.output.iter().copied())
+        .collect();
+
+    let undefined = names
+        .iter()
+        .copied()
+        .filter(|name| !defined.contains(name))
+        .collect();
+    let unreachable = names
+        .iter()
+        .copied()
+        .filter(|name| !produced.contains(name))
+        .collect();
+
+    let component_ids = strongly_connected(&transitions);
+    let count = component_ids.iter().copied().max().map_or(0, |c| c + 1);
+
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); count];
+    for (i, &c) in component_ids.iter().enumerate() {
+        members[c].push(i);
+    }
+
+    // Tarjan numbers components sinks first, so successors are always settled before use.
+    let mut cycles_on_path = vec![0; count];
+    let mut exponential = vec![false; count];
+
+    for (c, nodes) in members.iter().enumerate() {
+        let internal: Vec<u128> = nodes
+            .iter()
+            .map(|&i| {
+                transitions
+                    .successors(i)
+                    .filter(|(j, _)| component_ids[*j] == c)
+                    .map(|(_, k)| k)
+                    .sum()
+            })
+            .collect();
+
+        let has_cycle = internal.iter().any(|&k| k > 0);
+        let is_plain_cycle = internal.iter().all(|&k| k == 1);
+
+        let mut downstream = 0;
+        for &i in nodes {
+            for (j, _) in transitions.successors(i) {
+                let d = component_ids[j];
+                if d != c {
+                    downstream = downstream.max(cycles_on_path[d]);
+                    exponential[c] |= exponential[d];
+                }
+            }
+        }
+
+        exponential[c] |= has_cycle && !is_plain_cycle;
+        cycles_on_path[c] = downstream + has_cycle as usize;
+    }
+
+    let growth = (0..n)
+        .map(|i| {
+            let c = component_ids[i];
+            match (exponential[c], cycles_on_path[c]) {
+                (true, _) => Growth::Exponential,
+                (false, 0) => Growth::Extinct,
+                (false, k) => Growth::Polynomial(k - 1),
+            }
+        })
+        .collect();
+
+    let components = members
+        .iter()
+        .map(|nodes| nodes.iter().map(|&i| names[i]).collect())
+        .collect();
+
+    Analysis {
+        transitions,
+        undefined,
+        duplicates,
+        unreachable,
+        components,
+        growth,
+    }
+}
+
+/// Tarjan's algorithm, giving the component of every category.
+fn strongly_connected(transitions: &Transitions) -> Vec<usize> {
+    struct State {
+        next_index: usize,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        component: Vec<usize>,
+        components: usize,
+    }
+
+    fn visit(v: usize, transitions: &Transitions, state: &mut State) {
+        state.index[v] = Some(state.next_index);
+        state.low[v] = state.next_index;
+        state.next_index += 1;
+        state.stack.push(v);
+        state.on_stack[v] = true;
+
+        for (w, _) in transitions.successors(v) {
+            match state.index[w] {
+                None => {
+                    visit(w, transitions, state);
+                    state.low[v] = state.low[v].min(state.low[w]);
+                }
+                Some(index) if state.on_stack[w] => state.low[v] = state.low[v].min(index),
+                Some(_) => (),
+            }
+        }
+
+        if Some(state.low[v]) == state.index[v] {
+            while let Some(w) = state.stack.pop() {
+                state.on_stack[w] = false;
+                state.component[w] = state.components;
+                if w == v {
+                    break;
+                }
+            }
+            state.components += 1;
+        }
+    }
+
+    let n = transitions.categories().len();
+    let mut state = State {
+        next_index: 0,
+        index: vec![None; n],
+        low: vec![0; n],
+        on_stack: vec![false; n],
+        stack: Vec::new(),
+        component: vec![0; n],
+        components: 0,
+    };
+
+    for v in 0..n {
+        if state.index[v].is_none() {
+            visit(v, transitions, &mut state);
+        }
+    }
+
+    state.component
+}
+
+impl Analysis<'_> {
+    pub fn has_warnings(&self) -> bool {
+        !self.undefined.is_empty() || !self.duplicates.is_empty()
+    }
+
+    /// The rule graph, with edges labelled by multiplicity and categories coloured by growth.
+    pub fn to_dot(&self) -> String {
+        let names = self.transitions.categories();
+        let mut dot = String::from("digraph termites {\n");
+
+        for (i, name) in names.iter().enumerate() {
+            let colour = match self.growth[i] {
+                Growth::Extinct => "grey",
+                Growth::Polynomial(_) => "blue",
+                Growth::Exponential => "red",
+            };
+            let style = match self.undefined.contains(name) {
+                true => ", style=dashed",
+                false => "",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [color={}, tooltip=\"{}\"{}];",
+                name, colour, self.growth[i], style
+            );
+        }
+
+        for i in 0..names.len() {
+            for (j, k) in self.transitions.successors(i) {
+                let label = match k {
+                    1 => String::new(),
+                    _ => format!(" [label=\"{}\"]", k),
+                };
+                let _ = writeln!(dot, "    \"{}\" -> \"{}\"{};", names[i], names[j], label);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl fmt::Display for Analysis<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.undefined.is_empty() {
+            writeln!(f, "Undefined: {}", self.undefined.join(", "))?;
+        }
+        if !self.duplicates.is_empty() {
+            writeln!(f, "Defined more than once: {}", self.duplicates.join(", "))?;
+        }
+        if !self.unreachable.is_empty() {
+            writeln!(f, "Never produced: {}", self.unreachable.join(", "))?;
+        }
+
+        writeln!(f, "Components:")?;
+        for component in self.components.iter() {
+            writeln!(f, "    {{{}}}", component.join(", "))?;
+        }
+
+        writeln!(f, "Growth:")?;
+        for (name, growth) in self.transitions.categories().iter().zip(self.growth.iter()) {
+            writeln!(f, "    {}: {}", name, growth)?;
+        }
+
+        Ok(())
+    }
+}
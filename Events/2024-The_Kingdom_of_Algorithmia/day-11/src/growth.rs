@@ -0,0 +1,175 @@
+use crate::Rule;
+use std::collections::HashMap;
+
+type Matrix = Vec<Vec<u128>>;
+
+/// The rules as a matrix, where `matrix[i][j]` is how many termites of category `j` a single
+/// termite of category `i` turns into after one day.
+pub struct Transitions<'a> {
+    names: Vec<&'a str>,
+    index: HashMap<&'a str, usize>,
+    matrix: Matrix,
+}
+
+impl<'a> Transitions<'a> {
+    pub fn from_rules(rules: &[Rule<'a>]) -> Self {
+        let mut names: Vec<&str> = Vec::new();
+        let mut index: HashMap<&str, usize> = HashMap::new();
+
+        for name in rules
+            .iter()
+            .flat_map(|rule| std::iter::once(rule.input).chain(rule.output.iter().copied()))
+        {
+            index.entry(name).or_insert_with(|| {
+                names.push(name);
+                names.len() - 1
+            });
+        }
+
+        let mut matrix = vec![vec![0; names.len()]; names.len()];
+        for rule in rules {
+            for output in rule.output.iter() {
+                matrix[index[rule.input]][index[output]] += 1;
+            }
+        }
+
+        Transitions {
+            names,
+            index,
+            matrix,
+        }
+    }
+
+    pub fn categories(&self) -> &[&'a str] {
+        &self.names
+    }
+
+    /// Categories produced by one termite of category `i`, with how many of each.
+    pub fn successors(&self, i: usize) -> impl Iterator<Item = (usize, u128)> + '_ {
+        self.matrix[i]
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| **n > 0)
+            .map(|(j, n)| (j, *n))
+    }
+
+    /// Categories that a termite of the starter category can ever turn into, itself included.
+    fn reachable(&self, starter: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.names.len()];
+        let mut stack = vec![starter];
+        seen[starter] = true;
+
+        while let Some(i) = stack.pop() {
+            for (j, &n) in self.matrix[i].iter().enumerate() {
+                if n > 0 && !seen[j] {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+
+        (0..seen.len()).filter(|&i| seen[i]).collect()
+    }
+
+    /// Population after `days` days, computed with exponentiation by squaring. With a modulus the
+    /// count is reduced modulo it, otherwise `None` is returned if it would overflow a `u128`.
+    /// Unknown starters have no population at all.
+    pub fn population(&self, starter: &str, days: u64, modulus: Option<u64>) -> Option<u128> {
+        let Some(&start) = self.index.get(starter) else {
+            return Some(0);
+        };
+
+        let m = modulus.map(u128::from);
+        if m == Some(0) {
+            return None;
+        }
+
+        // Restricting to reachable categories keeps unrelated blow-ups from overflowing.
+        let keep = self.reachable(start);
+        let sub: Matrix = keep
+            .iter()
+            .map(|&i| keep.iter().map(|&j| self.matrix[i][j]).collect())
+            .collect();
+
+        let mut counts: Vec<u128> = keep.iter().map(|&i| (i == start) as u128).collect();
+        let mut power = sub;
+        let mut remaining = days;
+
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                counts = vector_times(&counts, &power, m)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                power = matrix_times(&power, &power, m)?;
+            }
+        }
+
+        counts
+            .into_iter()
+            .try_fold(0u128, |total, c| add(total, c, m))
+    }
+
+    /// Long-run factor by which the population of a starter grows each day, by power iteration on
+    /// normalised floating point counts. Averaging over many days smooths out periodic rule sets.
+    pub fn growth_rate(&self, starter: &str) -> Option<f64> {
+        let &start = self.index.get(starter)?;
+        let n = self.names.len();
+
+        let mut counts = vec![0.0; n];
+        counts[start] = 1.0;
+
+        const WARM_UP: usize = 1000;
+        const MEASURED: usize = 1000;
+        let mut log_growth = 0.0;
+
+        for day in 0..WARM_UP + MEASURED {
+            let mut next = vec![0.0; n];
+            for (i, &c) in counts.iter().enumerate().filter(|(_, c)| **c > 0.0) {
+                for (j, &k) in self.matrix[i].iter().enumerate() {
+                    next[j] += c * k as f64;
+                }
+            }
+
+            let total: f64 = next.iter().sum();
+            if total == 0.0 {
+                return Some(0.0);
+            }
+            if day >= WARM_UP {
+                log_growth += total.ln();
+            }
+
+            counts = next.into_iter().map(|c| c / total).collect();
+        }
+
+        Some((log_growth / MEASURED as f64).exp())
+    }
+}
+
+fn add(a: u128, b: u128, modulus: Option<u128>) -> Option<u128> {
+    match modulus {
+        Some(m) => Some((a + b) % m),
+        None => a.checked_add(b),
+    }
+}
+
+fn times(a: u128, b: u128, modulus: Option<u128>) -> Option<u128> {
+    match modulus {
+        Some(m) => Some((a % m) * (b % m) % m),
+        None => a.checked_mul(b),
+    }
+}
+
+fn vector_times(v: &[u128], matrix: &Matrix, modulus: Option<u128>) -> Option<Vec<u128>> {
+    let mut result = vec![0; v.len()];
+    for (i, &count) in v.iter().enumerate().filter(|(_, c)| **c > 0) {
+        for (j, &k) in matrix[i].iter().enumerate() {
+            result[j] = add(result[j], times(count, k, modulus)?, modulus)?;
+        }
+    }
+    Some(result)
+}
+
+fn matrix_times(a: &Matrix, b: &Matrix, modulus: Option<u128>) -> Option<Matrix> {
+    a.iter().map(|row| vector_times(row, b, modulus)).collect()
+}
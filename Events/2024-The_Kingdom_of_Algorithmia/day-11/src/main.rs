@@ -1,9 +1,20 @@
-use std::collections::HashMap;
+mod analysis;
+mod growth;
+
+use analysis::analyse;
+use growth::Transitions;
 
 fn main() {
     part_one();
     part_two();
     part_three();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => (),
+        Some("analyse") => check(&args[1..]),
+        Some(_) => forecast(&args),
+    }
 }
 
 fn part_one() {
@@ -24,7 +35,12 @@ fn part_three() {
     let input3 = include_str!("../input3.txt");
     let rules = parse(input3);
 
-    let counts: Vec<usize> = rules
+    let analysis = analyse(&rules);
+    if analysis.has_warnings() {
+        eprint!("{}", analysis);
+    }
+
+    let counts: Vec<u128> = rules
         .iter()
         .map(|rule| count_termites(&rules, rule.input, 20))
         .collect();
@@ -34,24 +50,59 @@ fn part_three() {
     println!("Part 3. Difference: {}", max_difference);
 }
 
-fn count_termites(rules: &[Rule], starter: &str, days: usize) -> usize {
-    let mut current_counts = HashMap::<&str, usize>::from([(starter, 1)]);
+/// Population of the part 3 ecosystem from a starter, for horizons like
+/// `day-11 A 1000000000000000 1000000007`, followed by each category's growth rate.
+fn forecast(args: &[String]) {
+    let input3 = include_str!("../input3.txt");
+    let rules = parse(input3);
+    let transitions = Transitions::from_rules(&rules);
 
-    for _day in 0..days {
-        let mut new_counts = HashMap::new();
+    let starter = args[0].as_str();
+    let days: u64 = args
+        .get(1)
+        .map_or(Some(20), |d| d.parse().ok())
+        .expect("Days should be an integer");
+    let modulus: Option<u64> = args
+        .get(2)
+        .map(|m| m.parse().expect("Modulus should be an integer"));
 
-        for rule in rules {
-            if let Some(input_count) = current_counts.get(&rule.input) {
-                for output in &rule.output {
-                    *new_counts.entry(*output).or_insert(0) += input_count;
-                }
-            }
+    match (transitions.population(starter, days, modulus), modulus) {
+        (Some(count), Some(m)) => {
+            println!("{} after {} days: {} (mod {})", starter, days, count, m)
         }
+        (Some(count), None) => println!("{} after {} days: {}", starter, days, count),
+        (None, _) => println!("{} after {} days: too large, give a modulus", starter, days),
+    }
 
-        current_counts = new_counts;
+    for category in transitions.categories() {
+        if let Some(rate) = transitions.growth_rate(category) {
+            println!("    {} grows by {:.6} per day", category, rate);
+        }
     }
+}
+
+/// Sanity check of an ecosystem, for `day-11 analyse [FILE] [--dot]`. Without a file the part 3
+/// rules are analysed, and `--dot` prints the rule graph instead of the report.
+fn check(args: &[String]) {
+    let dot = args.iter().any(|arg| arg == "--dot");
+    let input = match args.iter().find(|arg| *arg != "--dot") {
+        Some(path) => std::fs::read_to_string(path).expect("Rules file should be readable"),
+        None => include_str!("../input3.txt").to_string(),
+    };
+
+    let rules = parse(&input);
+    let analysis = analyse(&rules);
+
+    match dot {
+        true => print!("{}", analysis.to_dot()),
+        false => print!("{}", analysis),
+    }
+}
 
-    current_counts.values().sum()
+fn count_termites(rules: &[Rule], starter: &str, days: u64) -> u128 {
+    Transitions::from_rules(rules)
+        .population(starter, days, None)
+        .expect("Population should fit in a u128")
 }
 
 struct Rule<'a> {
@@ -59,7 +110,7 @@ struct Rule<'a> {
     output: Vec<&'a str>,
 }
 
-fn parse(input: &str) -> Vec<Rule> {
+fn parse(input: &str) -> Vec<Rule<'_>> {
     input
         .lines()
         .map(|line| {
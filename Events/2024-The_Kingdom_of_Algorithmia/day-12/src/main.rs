@@ -1,16 +1,37 @@
+mod planner;
+mod simulation;
+
 use glam::IVec2;
+use planner::{Objective, interception_options, plan_interception};
+use simulation::{Hit, target_hits};
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let objective = args
+        .iter()
+        .position(|arg| arg == "--objective")
+        .map_or(Ok(Objective::HighestAltitude), |i| {
+            args.get(i + 1)
+                .map_or(Err("--objective needs a value".to_string()), |o| o.parse())
+        })
+        .unwrap_or_else(|e| panic!("{}", e));
+
     part_one();
     part_two();
-    part_three();
+    part_three(objective);
+
+    match args.first().map(String::as_str) {
+        Some("verify") => verify(args.iter().any(|arg| arg == "--all")),
+        Some("plan") => plan(objective),
+        _ => (),
+    }
 }
 
 fn part_one() {
     let input = include_str!("../input1.txt");
     let targets = parse_targets(input);
 
-    let ranking_value: i32 = targets.into_iter().map(|t| ranking_score(t)).sum();
+    let ranking_value: i32 = targets.into_iter().map(ranking_score).sum();
     println!("Part 1. Ranking: {}", ranking_value);
 }
 
@@ -18,36 +39,155 @@ fn part_two() {
     let input = include_str!("../input2.txt");
     let targets = parse_targets(input);
 
-    let ranking_value: i32 = targets.into_iter().map(|t| ranking_score(t)).sum();
+    let ranking_value: i32 = targets.into_iter().map(ranking_score).sum();
     println!("Part 2. Ranking: {}", ranking_value);
 }
 
-fn part_three() {
+fn part_three(objective: Objective) {
     let input = include_str!("../input3.txt");
     let meteors = parse_meteors(input);
 
-    let total_ranking_score: i32 = meteors
+    let total_ranking_score: Result<i32, _> = meteors
         .into_iter()
-        .map(|m| ranking_score(meteor_intercept_position(m)))
+        .map(|m| plan_interception(m, objective).map(|hit| hit.ranking()))
         .sum();
-    println!("Part 3. Ranking: {}", total_ranking_score);
+
+    match total_ranking_score {
+        Ok(total) => println!("Part 3. Ranking: {}", total),
+        Err(e) => println!("Part 3. {}", e),
+    }
+}
+
+/// Every way to intercept each part 3 meteor, best first, for `day-12 plan [--objective NAME]`
+/// where the objective is `highest`, `lowest` or `earliest`.
+fn plan(objective: Objective) {
+    let meteors = parse_meteors(include_str!("../input3.txt"));
+
+    for meteor in meteors {
+        let options = interception_options(meteor, objective);
+        match options.is_empty() {
+            true => println!("Meteor {}: unreachable", meteor),
+            false => println!("Meteor {}: {} options", meteor, options.len()),
+        }
+        list_hits(&options);
+    }
+}
+
+/// Cross-checks the closed forms against the simulator on every input, for `day-12 verify`.
+/// With `--all` every solution found for each target and meteor is listed as well.
+fn verify(all: bool) {
+    let inputs = [include_str!("../input1.txt"), include_str!("../input2.txt")];
+
+    for (part, input) in inputs.into_iter().enumerate() {
+        let targets = parse_targets(input);
+        let mut mismatches = 0;
+
+        for target in targets.iter() {
+            let hits = target_hits(*target);
+            let simulated = hits.iter().map(Hit::ranking).min();
+            let formula = ranking_score(*target);
+
+            if simulated != Some(formula) {
+                mismatches += 1;
+                println!(
+                    "Target {}: formula ranks {}, simulation {}",
+                    target,
+                    formula,
+                    describe_ranking(simulated)
+                );
+            }
+            if all {
+                println!("Target {}:", target);
+                list_hits(&hits);
+            }
+        }
+
+        println!(
+            "Part {}. {} targets, {} mismatches",
+            part + 1,
+            targets.len(),
+            mismatches
+        );
+    }
+
+    let meteors = parse_meteors(include_str!("../input3.txt"));
+    let mut mismatches = 0;
+
+    for meteor in meteors.iter() {
+        let hits = interception_options(*meteor, Objective::HighestAltitude);
+        let simulated = hits.first();
+        let position = meteor_intercept_position(*meteor);
+        let formula = ranking_score(position);
+
+        let agrees =
+            simulated.is_some_and(|hit| hit.position == position && hit.ranking() == formula);
+        if !agrees {
+            mismatches += 1;
+            println!(
+                "Meteor {}: formula hits {} ranking {}, simulation {}",
+                meteor,
+                position,
+                formula,
+                simulated.map_or("misses".to_string(), |hit| format!(
+                    "hits {} ranking {}",
+                    hit.position,
+                    hit.ranking()
+                ))
+            );
+        }
+        if all {
+            println!("Meteor {}:", meteor);
+            list_hits(&hits);
+        }
+    }
+
+    println!(
+        "Part 3. {} meteors, {} mismatches",
+        meteors.len(),
+        mismatches
+    );
+}
+
+fn describe_ranking(ranking: Option<i32>) -> String {
+    ranking.map_or("misses".to_string(), |r| r.to_string())
+}
+
+fn list_hits(hits: &[Hit]) {
+    for hit in hits {
+        println!(
+            "    {} power {:>3}, launched at {:>3}, hits {} at time {:>3} ({:?}), ranking {}",
+            hit.segment_name(),
+            hit.power,
+            hit.delay,
+            hit.position,
+            hit.time,
+            hit.phase,
+            hit.ranking()
+        );
+    }
 }
 
 fn ranking_score(target: IVec2) -> i32 {
+    let (turret, power) = formula_shot(target);
+    turret * power
+}
+
+/// Turret (counting from 1) and power of the cheapest shot at a target, by the closed form.
+fn formula_shot(target: IVec2) -> (i32, i32) {
     let (x, y) = (target.x, target.y);
 
     if y - x >= 0 {
         let turret = y - x + 1;
         let power = x;
-        turret * power
+        (turret, power)
     } else if x > y && x <= 2 * y {
         let turret = 1;
         let power = y;
-        turret * power
+        (turret, power)
     } else {
         let turret = (x + y) % 3 + 1;
         let power = (x + y) / 3;
-        turret * power
+        (turret, power)
     }
 }
 
@@ -0,0 +1,118 @@
+use crate::simulation::{Hit, Meteor, Projectile, SEGMENTS, meteor_hits};
+use crate::{formula_shot, meteor_intercept_position};
+use glam::IVec2;
+use std::fmt;
+use std::str::FromStr;
+
+/// What makes one interception better than another. Ties are broken by the lowest ranking, then
+/// by the highest altitude.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Objective {
+    HighestAltitude,
+    LowestRanking,
+    EarliestHit,
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "highest" => Ok(Objective::HighestAltitude),
+            "lowest" => Ok(Objective::LowestRanking),
+            "earliest" => Ok(Objective::EarliestHit),
+            _ => Err(format!(
+                "Bad objective: {}. Expected highest, lowest or earliest",
+                s
+            )),
+        }
+    }
+}
+
+impl Objective {
+    /// Smaller keys are better.
+    fn key(&self, hit: &Hit) -> (i32, i32, i32) {
+        match self {
+            Objective::HighestAltitude => (-hit.position.y, hit.ranking(), 0),
+            Objective::LowestRanking => (hit.ranking(), -hit.position.y, 0),
+            Objective::EarliestHit => (hit.time, hit.ranking(), -hit.position.y),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Unreachable {
+    pub meteor: IVec2,
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Meteor at {} cannot be intercepted before it lands",
+            self.meteor
+        )
+    }
+}
+
+impl std::error::Error for Unreachable {}
+
+/// Every feasible interception of a meteor, best first for the objective.
+pub fn interception_options(meteor: IVec2, objective: Objective) -> Vec<Hit> {
+    let mut options = meteor_hits(meteor);
+    options.sort_by_key(|hit| objective.key(hit));
+    options
+}
+
+/// The best interception for the objective.
+pub fn plan_interception(meteor: IVec2, objective: Objective) -> Result<Hit, Unreachable> {
+    if objective == Objective::HighestAltitude
+        && let Some(hit) = closed_form_hit(meteor)
+    {
+        return Ok(hit);
+    }
+
+    interception_options(meteor, objective)
+        .into_iter()
+        .next()
+        .ok_or(Unreachable { meteor })
+}
+
+fn closed_form_hit(start: IVec2) -> Option<Hit> {
+    let position = meteor_intercept_position(start);
+    let (turret, power) = formula_shot(position);
+    if !(1..=SEGMENTS.len() as i32).contains(&turret) || power < 1 {
+        return None;
+    }
+    let segment = turret as usize - 1;
+
+    let time = start.x - position.x;
+    let delay = time - position.x;
+    if delay < 0 {
+        return None;
+    }
+
+    let mut meteor = Meteor::new(start);
+    let mut projectile = Projectile::launch(segment, power);
+    let mut phase = projectile.phase();
+
+    while meteor.time < time {
+        meteor.step();
+        if meteor.time > delay {
+            phase = projectile.phase();
+            projectile.step();
+        }
+        if meteor.landed() || projectile.landed() {
+            return None;
+        }
+    }
+
+    (meteor.position == projectile.position).then_some(Hit {
+        segment,
+        power,
+        delay,
+        time,
+        position,
+        phase,
+    })
+}
@@ -0,0 +1,165 @@
+use glam::IVec2;
+
+/// Launch points of the catapult segments A, B and C.
+pub const SEGMENTS: [IVec2; 3] = [IVec2::new(0, 0), IVec2::new(0, 1), IVec2::new(0, 2)];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Up,
+    Flat,
+    Down,
+}
+
+/// A projectile flies `power` steps diagonally up, `power` steps flat, then falls diagonally until
+/// it reaches the ground. It moves one column per step, so its column is always its flight time.
+pub struct Projectile {
+    pub position: IVec2,
+    pub time: i32,
+    power: i32,
+}
+
+impl Projectile {
+    pub fn launch(segment: usize, power: i32) -> Self {
+        Projectile {
+            position: SEGMENTS[segment],
+            time: 0,
+            power,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        if self.time < self.power {
+            Phase::Up
+        } else if self.time < 2 * self.power {
+            Phase::Flat
+        } else {
+            Phase::Down
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.position += match self.phase() {
+            Phase::Up => IVec2::new(1, 1),
+            Phase::Flat => IVec2::new(1, 0),
+            Phase::Down => IVec2::new(1, -1),
+        };
+        self.time += 1;
+    }
+
+    pub fn landed(&self) -> bool {
+        self.position.y < 0
+    }
+}
+
+/// Meteors fall diagonally towards the catapult, one step down and to the left per time unit.
+pub struct Meteor {
+    pub position: IVec2,
+    pub time: i32,
+}
+
+impl Meteor {
+    pub fn new(position: IVec2) -> Self {
+        Meteor { position, time: 0 }
+    }
+
+    pub fn step(&mut self) {
+        self.position -= IVec2::ONE;
+        self.time += 1;
+    }
+
+    pub fn landed(&self) -> bool {
+        self.position.y < 0 || self.position.x < 0
+    }
+}
+
+/// A shot launched at `delay` that strikes `position` at absolute `time`.
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+    pub segment: usize,
+    pub power: i32,
+    pub delay: i32,
+    pub time: i32,
+    pub position: IVec2,
+    pub phase: Phase,
+}
+
+impl Hit {
+    pub fn ranking(&self) -> i32 {
+        (self.segment as i32 + 1) * self.power
+    }
+
+    pub fn segment_name(&self) -> char {
+        (b'A' + self.segment as u8) as char
+    }
+}
+
+/// Every shot fired at time 0 that strikes a stationary target.
+pub fn target_hits(target: IVec2) -> Vec<Hit> {
+    let mut hits = Vec::new();
+
+    for (segment, origin) in SEGMENTS.iter().enumerate() {
+        // The projectile never climbs higher than its launch point plus its power.
+        for power in (target.y - origin.y).max(1)..=target.x {
+            let mut projectile = Projectile::launch(segment, power);
+            let mut phase = projectile.phase();
+
+            while projectile.position.x < target.x && !projectile.landed() {
+                phase = projectile.phase();
+                projectile.step();
+            }
+
+            if projectile.position == target {
+                hits.push(Hit {
+                    segment,
+                    power,
+                    delay: 0,
+                    time: projectile.time,
+                    position: target,
+                    phase,
+                });
+            }
+        }
+    }
+
+    hits
+}
+
+/// Every shot that strikes a falling meteor, earliest first.
+pub fn meteor_hits(start: IVec2) -> Vec<Hit> {
+    let mut hits = Vec::new();
+
+    for segment in 0..SEGMENTS.len() {
+        for power in 1..=start.x {
+            let mut projectile = Projectile::launch(segment, power);
+
+            loop {
+                let phase = projectile.phase();
+                projectile.step();
+
+                let position = projectile.position;
+                if projectile.landed() || position.x > start.x {
+                    break;
+                }
+                if phase == Phase::Up && power > projectile.time {
+                    continue;
+                }
+
+                let time = start.x - position.x;
+                let delay = time - projectile.time;
+                if start.y - position.y == time && delay >= 0 {
+                    hits.push(Hit {
+                        segment,
+                        power,
+                        delay,
+                        time,
+                        position,
+                        phase,
+                    });
+                }
+            }
+        }
+    }
+
+    hits.sort_by_key(|hit| (hit.time, hit.segment, hit.power));
+    hits
+}
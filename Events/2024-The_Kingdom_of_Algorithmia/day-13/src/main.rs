@@ -1,90 +1,132 @@
+mod route;
+
 use glam::IVec2;
-use std::{cmp::Reverse, collections::HashMap};
+use route::{Route, render, search_from};
+use std::collections::HashMap;
 
 const OFFSETS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
 
-fn main() {
-    part_one();
-    part_two();
-    part_three();
+struct Settings {
+    levels: i32,
+    step_cost: usize,
+    show: bool,
 }
 
-fn part_one() {
-    let input = include_str!("../input1.txt");
-    let grid = parse(input);
-    let times = shortest_times(&grid, grid.starts[0]);
-
-    if let Some(time) = times.get(&grid.goals[0]) {
-        println!("Part 1. Time: {}", time);
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            levels: 10,
+            step_cost: 1,
+            show: false,
+        }
     }
 }
 
-fn part_two() {
-    let input = include_str!("../input2.txt");
-    let grid = parse(input);
-    let times = shortest_times(&grid, grid.starts[0]);
+impl Settings {
+    fn from_args() -> Self {
+        let mut settings = Settings::default();
+        let mut args = std::env::args().skip(1);
+
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .expect("Option should be followed by an integer")
+            };
+            match flag.as_str() {
+                "--show" => settings.show = true,
+                "--levels" => settings.levels = value() as i32,
+                "--step-cost" => settings.step_cost = value(),
+                _ => panic!(
+                    "Unknown option {}. Expected --levels, --step-cost or --show",
+                    flag
+                ),
+            }
+        }
 
-    if let Some(time) = times.get(&grid.goals[0]) {
-        println!("Part 2. Time: {}", time);
+        assert!(settings.levels > 0, "There should be at least one level");
+        settings
     }
-}
 
-fn part_three() {
-    let input = include_str!("../input3.txt");
-    let grid = parse(input);
-    let times = shortest_times(&grid, grid.goals[0]);
-
-    if let Some(shortest_time) = times
-        .into_iter()
-        .filter(|(pos, _)| grid.starts.contains(pos))
-        .map(|(_, time)| time)
-        .min()
-    {
-        println!("Part 2. Time: {}", shortest_time);
+    /// Time to step between platforms, turning the levels the short way around the cycle.
+    fn step_time(&self, from: i32, to: i32) -> usize {
+        let height_diff = (to - from).rem_euclid(self.levels);
+        let height_shift = height_diff.min(self.levels - height_diff) as usize;
+        height_shift + self.step_cost
     }
 }
 
-fn shortest_times(grid: &Grid, start: IVec2) -> HashMap<IVec2, usize> {
-    let mut prio_queue = priority_queue::PriorityQueue::new();
-    let mut times = HashMap::from([(start, 0usize)]);
+fn main() {
+    let settings = Settings::from_args();
 
-    prio_queue.push(start, Reverse(0usize));
+    part_one(&settings);
+    part_two(&settings);
+    part_three(&settings);
+}
 
-    while let Some((current_node, Reverse(current_time))) = prio_queue.pop() {
-        let current_elevation = grid.nodes.get(&current_node).expect("Should always exist");
+fn part_one(settings: &Settings) {
+    let input = include_str!("../input1.txt");
+    let grid = parse(input);
+    solve(1, &grid, settings);
+}
 
-        let neighbours = OFFSETS
-            .iter()
-            .map(|off| current_node + off)
-            .filter(|n| grid.nodes.contains_key(n));
+fn part_two(settings: &Settings) {
+    let input = include_str!("../input2.txt");
+    let grid = parse(input);
+    solve(2, &grid, settings);
+}
 
-        for neighbour in neighbours {
-            let neighbour_elevation = grid.nodes.get(&neighbour).expect("Should always exist");
+fn part_three(settings: &Settings) {
+    let input = include_str!("../input3.txt");
+    let grid = parse(input);
+    solve(3, &grid, settings);
+}
 
-            let height_diff = (neighbour_elevation - current_elevation).abs();
-            let height_shift = height_diff.min(10 - height_diff) as usize;
-            let neighbour_time = current_time + height_shift + 1;
+/// Quickest route from any start to any goal. With `--show` the best time from every start is
+/// listed, along with the grid and the winning route.
+fn solve(part: usize, grid: &Grid, settings: &Settings) {
+    let search = search_from(grid, &grid.goals, settings);
+    let routes: Vec<(IVec2, Option<Route>)> = grid
+        .starts
+        .iter()
+        .map(|&start| (start, search.route(start)))
+        .collect();
+
+    let Some(best) = routes
+        .iter()
+        .filter_map(|(_, route)| route.as_ref())
+        .min_by_key(|route| route.time)
+    else {
+        println!("Part {}. No route from any S to any E", part);
+        return;
+    };
+
+    println!("Part {}. Time: {}", part, best.time);
+
+    if !settings.show {
+        return;
+    }
 
-            match times.get(&neighbour) {
-                Some(&old_time) if neighbour_time < old_time => {
-                    times.insert(neighbour, neighbour_time);
-                    prio_queue.change_priority(&neighbour, Reverse(neighbour_time));
-                }
-                None => {
-                    times.insert(neighbour, neighbour_time);
-                    prio_queue.push(neighbour, Reverse(neighbour_time));
-                }
-                _ => (),
-            }
+    println!(
+        "    {} starts, {} goals. Best from S {} to E {}",
+        grid.starts.len(),
+        grid.goals.len(),
+        best.start(),
+        best.end()
+    );
+    for (start, route) in routes.iter() {
+        match route {
+            Some(route) => println!("    S {}: {} to E {}", start, route.time, route.end()),
+            None => println!("    S {}: no route", start),
         }
     }
-
-    times
+    println!("{}", render(grid, best));
 }
 
 #[derive(Debug)]
 struct Grid {
     nodes: HashMap<IVec2, i32>,
+    cells: Vec<Vec<char>>,
     starts: Vec<IVec2>,
     goals: Vec<IVec2>,
 }
@@ -108,16 +150,21 @@ fn parse(input: &str) -> Grid {
                     goals.push(pos);
                 }
                 '#' | ' ' => (),
-                _ => {
-                    let level = c.to_digit(10).unwrap();
+                // Designs with more than ten levels continue from `a`.
+                '0'..='9' | 'a'..='z' => {
+                    let level = c.to_digit(36).unwrap();
                     nodes.insert(pos, level as i32);
                 }
+                _ => panic!("Unexpected character {} at {}", c, pos),
             }
         }
     }
 
+    let cells = input.lines().map(|line| line.chars().collect()).collect();
+
     Grid {
         nodes,
+        cells,
         starts,
         goals,
     }
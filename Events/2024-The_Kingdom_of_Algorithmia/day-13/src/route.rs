@@ -0,0 +1,109 @@
+use crate::{Grid, OFFSETS, Settings};
+use glam::IVec2;
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Shortest times from the nearest of several sources, with the next step back towards it.
+pub struct Search {
+    pub times: HashMap<IVec2, usize>,
+    towards_source: HashMap<IVec2, IVec2>,
+}
+
+pub struct Route {
+    pub time: usize,
+    pub path: Vec<IVec2>,
+}
+
+impl Route {
+    pub fn start(&self) -> IVec2 {
+        self.path[0]
+    }
+
+    pub fn end(&self) -> IVec2 {
+        self.path[self.path.len() - 1]
+    }
+}
+
+/// Dijkstra from all sources at once. Moving costs the same in both directions, so a search from
+/// the goals also gives the best route from every start.
+pub fn search_from(grid: &Grid, sources: &[IVec2], settings: &Settings) -> Search {
+    let mut prio_queue = priority_queue::PriorityQueue::new();
+    let mut times = HashMap::new();
+    let mut towards_source = HashMap::new();
+
+    for &source in sources {
+        times.insert(source, 0usize);
+        prio_queue.push(source, Reverse(0usize));
+    }
+
+    while let Some((current_node, Reverse(current_time))) = prio_queue.pop() {
+        let current_elevation = grid.nodes.get(&current_node).expect("Should always exist");
+
+        let neighbours = OFFSETS
+            .iter()
+            .map(|off| current_node + off)
+            .filter(|n| grid.nodes.contains_key(n));
+
+        for neighbour in neighbours {
+            let neighbour_elevation = grid.nodes.get(&neighbour).expect("Should always exist");
+            let neighbour_time =
+                current_time + settings.step_time(*current_elevation, *neighbour_elevation);
+
+            match times.get(&neighbour) {
+                Some(&old_time) if neighbour_time < old_time => {
+                    times.insert(neighbour, neighbour_time);
+                    towards_source.insert(neighbour, current_node);
+                    prio_queue.change_priority(&neighbour, Reverse(neighbour_time));
+                }
+                None => {
+                    times.insert(neighbour, neighbour_time);
+                    towards_source.insert(neighbour, current_node);
+                    prio_queue.push(neighbour, Reverse(neighbour_time));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    Search {
+        times,
+        towards_source,
+    }
+}
+
+impl Search {
+    /// The route from `from` to whichever source is closest to it.
+    pub fn route(&self, from: IVec2) -> Option<Route> {
+        let &time = self.times.get(&from)?;
+        let mut path = vec![from];
+
+        while let Some(&next) = self.towards_source.get(&path[path.len() - 1]) {
+            path.push(next);
+        }
+
+        Some(Route { time, path })
+    }
+}
+
+/// The grid with the route drawn over it as arrows, keeping the start and end markers.
+pub fn render(grid: &Grid, route: &Route) -> String {
+    let mut cells = grid.cells.clone();
+
+    for (from, to) in route.path.iter().zip(route.path.iter().skip(1)) {
+        if *from == route.start() {
+            continue;
+        }
+        let arrow = match *to - *from {
+            IVec2::X => '>',
+            IVec2::NEG_X => '<',
+            IVec2::Y => 'v',
+            _ => '^',
+        };
+        cells[from.y as usize][from.x as usize] = arrow;
+    }
+
+    cells
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,221 @@
+use crate::Tree;
+use glam::IVec3;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Part {
+    Trunk,
+    Branch,
+    Leaf,
+    Highlight,
+}
+
+impl Part {
+    const ALL: [Part; 4] = [Part::Trunk, Part::Branch, Part::Leaf, Part::Highlight];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Part::Trunk => "trunk",
+            Part::Branch => "branch",
+            Part::Leaf => "leaf",
+            Part::Highlight => "highlight",
+        }
+    }
+
+    fn colour(&self) -> [u8; 3] {
+        match self {
+            Part::Trunk => [110, 70, 40],
+            Part::Branch => [170, 120, 70],
+            Part::Leaf => [60, 160, 60],
+            Part::Highlight => [220, 40, 40],
+        }
+    }
+}
+
+/// Every segment of the tree with the part it belongs to, sorted so exports are reproducible.
+pub fn classify(tree: &Tree, highlight: Option<IVec3>) -> Vec<(IVec3, Part)> {
+    let mut voxels: Vec<(IVec3, Part)> = tree
+        .segments
+        .iter()
+        .map(|&segment| {
+            let part = if Some(segment) == highlight {
+                Part::Highlight
+            } else if tree.leaves.contains(&segment) {
+                Part::Leaf
+            } else if tree.is_trunk(&segment) {
+                Part::Trunk
+            } else {
+                Part::Branch
+            };
+            (segment, part)
+        })
+        .collect();
+
+    voxels.sort_by_key(|(segment, _)| segment.to_array());
+    voxels
+}
+
+/// Corners of the unit cube face facing each direction, counter-clockwise seen from outside.
+const FACES: [(IVec3, [IVec3; 4]); 6] = [
+    (
+        IVec3::X,
+        [
+            IVec3::new(1, 0, 0),
+            IVec3::new(1, 1, 0),
+            IVec3::new(1, 1, 1),
+            IVec3::new(1, 0, 1),
+        ],
+    ),
+    (
+        IVec3::NEG_X,
+        [
+            IVec3::new(0, 0, 0),
+            IVec3::new(0, 0, 1),
+            IVec3::new(0, 1, 1),
+            IVec3::new(0, 1, 0),
+        ],
+    ),
+    (
+        IVec3::Y,
+        [
+            IVec3::new(0, 1, 0),
+            IVec3::new(0, 1, 1),
+            IVec3::new(1, 1, 1),
+            IVec3::new(1, 1, 0),
+        ],
+    ),
+    (
+        IVec3::NEG_Y,
+        [
+            IVec3::new(0, 0, 0),
+            IVec3::new(1, 0, 0),
+            IVec3::new(1, 0, 1),
+            IVec3::new(0, 0, 1),
+        ],
+    ),
+    (
+        IVec3::Z,
+        [
+            IVec3::new(0, 0, 1),
+            IVec3::new(1, 0, 1),
+            IVec3::new(1, 1, 1),
+            IVec3::new(0, 1, 1),
+        ],
+    ),
+    (
+        IVec3::NEG_Z,
+        [
+            IVec3::new(0, 0, 0),
+            IVec3::new(0, 1, 0),
+            IVec3::new(1, 1, 0),
+            IVec3::new(1, 0, 0),
+        ],
+    ),
+];
+
+/// The visible surface of the voxels as quads. Faces between two voxels are left out.
+///
+/// The tree grows along z, but most viewers expect y to point up, so vertices are rotated to put
+/// the tree upright.
+pub struct Mesh {
+    vertices: Vec<IVec3>,
+    faces: Vec<([usize; 4], Part)>,
+}
+
+impl Mesh {
+    pub fn from_voxels(voxels: &[(IVec3, Part)]) -> Self {
+        let occupied: HashSet<IVec3> = voxels.iter().map(|(segment, _)| *segment).collect();
+        let mut index: HashMap<IVec3, usize> = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+
+        for &(segment, part) in voxels {
+            for (normal, corners) in FACES.iter() {
+                if occupied.contains(&(segment + *normal)) {
+                    continue;
+                }
+
+                let quad = corners.map(|corner| {
+                    let vertex = segment + corner;
+                    *index.entry(vertex).or_insert_with(|| {
+                        vertices.push(vertex);
+                        vertices.len() - 1
+                    })
+                });
+                faces.push((quad, part));
+            }
+        }
+
+        Mesh { vertices, faces }
+    }
+
+    fn upright(vertex: IVec3) -> IVec3 {
+        IVec3::new(vertex.x, vertex.z, -vertex.y)
+    }
+
+    /// Wavefront OBJ, with one material group per part from the named material library.
+    pub fn to_obj(&self, material_library: &str) -> String {
+        let mut obj = format!("mtllib {}\n", material_library);
+
+        for vertex in self.vertices.iter().copied().map(Mesh::upright) {
+            let _ = writeln!(obj, "v {} {} {}", vertex.x, vertex.y, vertex.z);
+        }
+
+        for part in Part::ALL {
+            let _ = writeln!(obj, "usemtl {}", part.name());
+            for (quad, _) in self.faces.iter().filter(|(_, p)| *p == part) {
+                // OBJ indices start at one.
+                let _ = writeln!(
+                    obj,
+                    "f {} {} {} {}",
+                    quad[0] + 1,
+                    quad[1] + 1,
+                    quad[2] + 1,
+                    quad[3] + 1
+                );
+            }
+        }
+
+        obj
+    }
+
+    /// ASCII PLY with a colour on every face.
+    pub fn to_ply(&self) -> String {
+        let mut ply = String::from("ply\nformat ascii 1.0\n");
+        let _ = writeln!(ply, "element vertex {}", self.vertices.len());
+        ply.push_str("property int x\nproperty int y\nproperty int z\n");
+        let _ = writeln!(ply, "element face {}", self.faces.len());
+        ply.push_str("property list uchar int vertex_indices\n");
+        ply.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\n");
+        ply.push_str("end_header\n");
+
+        for vertex in self.vertices.iter().copied().map(Mesh::upright) {
+            let _ = writeln!(ply, "{} {} {}", vertex.x, vertex.y, vertex.z);
+        }
+
+        for (quad, part) in self.faces.iter() {
+            let [r, g, b] = part.colour();
+            let _ = writeln!(
+                ply,
+                "4 {} {} {} {} {} {} {}",
+                quad[0], quad[1], quad[2], quad[3], r, g, b
+            );
+        }
+
+        ply
+    }
+}
+
+/// Materials for the parts, referenced by `Mesh::to_obj`.
+pub fn to_mtl() -> String {
+    let mut mtl = String::new();
+
+    for part in Part::ALL {
+        let [r, g, b] = part.colour().map(|c| c as f32 / 255.0);
+        let _ = writeln!(mtl, "newmtl {}", part.name());
+        let _ = writeln!(mtl, "Kd {:.3} {:.3} {:.3}\n", r, g, b);
+    }
+
+    mtl
+}
@@ -1,5 +1,11 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+mod export;
+mod murkiness;
+mod validate;
+
+use murkiness::trunk_murkiness;
+use std::collections::HashSet;
 use std::num::ParseIntError;
+use std::path::Path;
 use std::str::FromStr;
 
 use glam::IVec3;
@@ -8,6 +14,19 @@ fn main() {
     part_one();
     part_two();
     part_three();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["export", directory] => {
+            export_models(Path::new(directory)).expect("Models should be writable")
+        }
+        ["profile"] => profile(),
+        ["validate"] => check_plan(include_str!("../input3.txt")),
+        ["validate", path] => {
+            check_plan(&std::fs::read_to_string(path).expect("Plan should be readable"))
+        }
+        _ => (),
+    }
 }
 
 fn part_one() {
@@ -18,7 +37,7 @@ fn part_one() {
     let mut current = IVec3::ZERO;
 
     for m in moves {
-        current = current + m.dist * m.dir;
+        current += m.dist * m.dir;
         max_height = max_height.max(current.z);
     }
 
@@ -27,82 +46,109 @@ fn part_one() {
 
 fn part_two() {
     let input = include_str!("../input2.txt");
-    let branches: Vec<Vec<Move>> = input.lines().map(|line| parse(line)).collect();
-
-    let mut unique_segments = HashSet::new();
-
-    for branch in branches {
-        let mut current = IVec3::ZERO;
-        for m in branch {
-            for _ in 0..m.dist {
-                current = current + m.dir;
-                unique_segments.insert(current);
-            }
-        }
-    }
+    let tree = grow(&parse_branches(input));
 
-    println!("Part 2. Segments: {}", unique_segments.len());
+    println!("Part 2. Segments: {}", tree.segments.len());
 }
 
 fn part_three() {
     let input = include_str!("../input3.txt");
-    let branches: Vec<Vec<Move>> = input.lines().map(|line| parse(line)).collect();
+    let tree = grow(&parse_branches(input));
 
-    let mut unique_leaves = HashSet::new();
-    let mut unique_segments = HashSet::new();
+    let min_murkiness = trunk_murkiness(&tree)
+        .into_iter()
+        .map(|(_, murkiness)| murkiness)
+        .min()
+        .expect("Should exist if we have any trunk segments");
 
-    for branch in branches {
-        let mut current = IVec3::ZERO;
-        for m in branch {
-            for _ in 0..m.dist {
-                current = current + m.dir;
-                unique_segments.insert(current);
-            }
-        }
+    println!("Part 3. Minimum murkiness: {}", min_murkiness);
+}
 
-        unique_leaves.insert(current);
+/// Lists everything wrong with a growth plan, for `day-14 validate [FILE]`. Without a file the
+/// part 3 plan is checked.
+fn check_plan(input: &str) {
+    let problems = validate::validate(input);
+
+    for problem in problems.iter() {
+        println!("{}", problem);
     }
+    println!("{} problems found", problems.len());
+}
 
-    let mut trunk_murkiness: HashMap<IVec3, usize> = unique_segments
-        .iter()
-        .filter(|seg| seg.z > 0 && seg.x == 0 && seg.y == 0 && !unique_leaves.contains(seg))
-        .map(|trunk| (*trunk, 0))
-        .collect();
+/// Murkiness of every part 3 trunk segment from the ground up, for `day-14 profile`.
+fn profile() {
+    let tree = grow(&parse_branches(include_str!("../input3.txt")));
+    let murkiness = trunk_murkiness(&tree);
+
+    let Some(&(_, min)) = murkiness.iter().min_by_key(|(_, m)| *m) else {
+        println!("No trunk segments");
+        return;
+    };
+    let max = murkiness.iter().map(|(_, m)| *m).max().unwrap_or(min);
+
+    for (trunk, m) in murkiness.iter() {
+        // Bars show how far above the minimum each segment is.
+        let bar = (m - min) * 50 / (max - min).max(1);
+        let marker = if *m == min { " <- minimum" } else { "" };
+        println!("{:>5} {:>10} {}{}", trunk.z, m, "#".repeat(bar), marker);
+    }
+}
 
-    // BFS from each leaf, and add distance to each trunk segment encountered
-    for leaf in &unique_leaves {
-        let mut queue = VecDeque::from([(*leaf, 0)]);
-        let mut seen = HashSet::from([*leaf]);
+/// Writes the part 2 and part 3 trees as OBJ (with materials) and PLY models into a directory,
+/// for `day-14 export DIRECTORY`. The part 3 trunk segment with the least murkiness is highlighted.
+fn export_models(directory: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(directory)?;
+
+    let part_two = grow(&parse_branches(include_str!("../input2.txt")));
+    let part_three = grow(&parse_branches(include_str!("../input3.txt")));
+    let best_trunk = trunk_murkiness(&part_three)
+        .into_iter()
+        .min_by_key(|(trunk, murkiness)| (*murkiness, trunk.z))
+        .map(|(trunk, _)| trunk);
+
+    for (name, tree, highlight) in [("part2", part_two, None), ("part3", part_three, best_trunk)] {
+        let mesh = export::Mesh::from_voxels(&export::classify(&tree, highlight));
+        let materials = format!("{}.mtl", name);
+
+        std::fs::write(
+            directory.join(format!("{}.obj", name)),
+            mesh.to_obj(&materials),
+        )?;
+        std::fs::write(directory.join(&materials), export::to_mtl())?;
+        std::fs::write(directory.join(format!("{}.ply", name)), mesh.to_ply())?;
+    }
 
-        while let Some((current_leaf, current_dist)) = queue.pop_front() {
-            if let Some(trunk) = trunk_murkiness.get_mut(&current_leaf) {
-                *trunk += current_dist;
-            }
+    Ok(())
+}
+
+struct Tree {
+    segments: HashSet<IVec3>,
+    leaves: HashSet<IVec3>,
+}
+
+impl Tree {
+    fn is_trunk(&self, segment: &IVec3) -> bool {
+        segment.z > 0 && segment.x == 0 && segment.y == 0 && !self.leaves.contains(segment)
+    }
+}
 
-            for offset in [
-                IVec3::Z,
-                IVec3::NEG_Z,
-                IVec3::Y,
-                IVec3::NEG_Y,
-                IVec3::NEG_X,
-                IVec3::X,
-            ] {
-                let neighbour = current_leaf + offset;
-
-                if unique_segments.contains(&neighbour) && !seen.contains(&neighbour) {
-                    seen.insert(neighbour);
-                    queue.push_back((neighbour, current_dist + 1));
-                }
+fn grow(branches: &[Vec<Move>]) -> Tree {
+    let mut leaves = HashSet::new();
+    let mut segments = HashSet::new();
+
+    for branch in branches {
+        let mut current = IVec3::ZERO;
+        for m in branch {
+            for _ in 0..m.dist {
+                current += m.dir;
+                segments.insert(current);
             }
         }
-    }
 
-    let min_murkiness = trunk_murkiness
-        .values()
-        .min()
-        .expect("Should exist if we have any trunk segments");
+        leaves.insert(current);
+    }
 
-    println!("Part 3. Minimum murkiness: {}", min_murkiness);
+    Tree { segments, leaves }
 }
 
 struct Move {
@@ -114,7 +160,9 @@ impl FromStr for Move {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (dir_char, dist_str) = s.split_at(1);
+        let (dir_char, dist_str) = s
+            .split_at_checked(1)
+            .ok_or_else(|| format!("Bad move: {:?}", s))?;
         let dist = dist_str
             .parse::<i32>()
             .map_err(|e: ParseIntError| e.to_string())?;
@@ -137,3 +185,7 @@ fn parse(input: &str) -> Vec<Move> {
         .map(|m| Move::from_str(m).expect("Invalid move"))
         .collect()
 }
+
+fn parse_branches(input: &str) -> Vec<Vec<Move>> {
+    input.lines().map(parse).collect()
+}
@@ -0,0 +1,99 @@
+use crate::Tree;
+use glam::IVec3;
+use std::collections::{HashMap, VecDeque};
+
+const OFFSETS: [IVec3; 6] = [
+    IVec3::Z,
+    IVec3::NEG_Z,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::NEG_X,
+    IVec3::X,
+];
+
+/// The segments as an adjacency list, so searches work on indices rather than hashed positions.
+struct SegmentGraph {
+    positions: Vec<IVec3>,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl SegmentGraph {
+    fn new(tree: &Tree) -> Self {
+        let mut positions: Vec<IVec3> = tree.segments.iter().copied().collect();
+        positions.sort_by_key(|p| p.to_array());
+
+        let index: HashMap<IVec3, usize> =
+            positions.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+
+        let neighbours = positions
+            .iter()
+            .map(|p| {
+                OFFSETS
+                    .iter()
+                    .filter_map(|offset| index.get(&(*p + *offset)).copied())
+                    .collect()
+            })
+            .collect();
+
+        SegmentGraph {
+            positions,
+            neighbours,
+        }
+    }
+}
+
+/// Murkiness of every trunk segment, the summed distance to all leaves, ordered from the ground
+/// up.
+///
+/// A trunk is far shorter than the number of leaves on a big tree, so there is one search from
+/// each trunk segment rather than one from each leaf. Each search stops once every leaf has been
+/// reached, and the buffers are reused between searches by stamping visits with the trunk index.
+pub fn trunk_murkiness(tree: &Tree) -> Vec<(IVec3, usize)> {
+    let graph = SegmentGraph::new(tree);
+    let is_leaf: Vec<bool> = graph
+        .positions
+        .iter()
+        .map(|p| tree.leaves.contains(p))
+        .collect();
+    let leaf_count = is_leaf.iter().filter(|leaf| **leaf).count();
+
+    let mut trunk: Vec<usize> = (0..graph.positions.len())
+        .filter(|&i| tree.is_trunk(&graph.positions[i]))
+        .collect();
+    trunk.sort_by_key(|&i| graph.positions[i].z);
+
+    let mut visited_by = vec![usize::MAX; graph.positions.len()];
+    let mut queue = VecDeque::new();
+
+    trunk
+        .iter()
+        .enumerate()
+        .map(|(search, &start)| {
+            queue.clear();
+            queue.push_back((start, 0));
+            visited_by[start] = search;
+
+            let mut murkiness = 0;
+            let mut leaves_found = 0;
+
+            while let Some((current, dist)) = queue.pop_front() {
+                if is_leaf[current] {
+                    murkiness += dist;
+                    leaves_found += 1;
+                    if leaves_found == leaf_count {
+                        break;
+                    }
+                }
+
+                for &neighbour in graph.neighbours[current].iter() {
+                    if visited_by[neighbour] != search {
+                        visited_by[neighbour] = search;
+                        queue.push_back((neighbour, dist + 1));
+                    }
+                }
+            }
+
+            (graph.positions[start], murkiness)
+        })
+        .collect()
+}
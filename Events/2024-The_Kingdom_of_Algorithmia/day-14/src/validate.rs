@@ -0,0 +1,124 @@
+use crate::Move;
+use glam::IVec3;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProblemKind {
+    Unparsable(String),
+    BadLength(i32),
+    OffTrunk,
+    BelowGround(IVec3),
+    Retraces(IVec3),
+    /// Grows into a segment of an earlier branch without following the same path to it.
+    Collides {
+        at: IVec3,
+        line: usize,
+    },
+}
+
+/// Lines and moves are numbered from one, as they would be counted in the plan.
+#[derive(Debug)]
+pub struct Problem {
+    pub line: usize,
+    pub index: usize,
+    pub text: String,
+    pub kind: ProblemKind,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, move {} ({}): ",
+            self.line, self.index, self.text
+        )?;
+        match &self.kind {
+            ProblemKind::Unparsable(e) => write!(f, "{}", e),
+            ProblemKind::BadLength(dist) => write!(f, "has length {}, expected at least 1", dist),
+            ProblemKind::OffTrunk => write!(f, "branch does not start up the trunk"),
+            ProblemKind::BelowGround(at) => write!(f, "grows below ground at {}", at),
+            ProblemKind::Retraces(at) => write!(f, "retraces its own branch at {}", at),
+            ProblemKind::Collides { at, line } => {
+                write!(f, "collides with the branch on line {} at {}", line, at)
+            }
+        }
+    }
+}
+
+/// Checks every branch of a growth plan. A branch may share segments with earlier ones only while
+/// it grows along the same path, as when leaving the trunk at the same height.
+pub fn validate(input: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    // The branch that first grew each segment, and the segment it grew from.
+    let mut occupied: HashMap<IVec3, (usize, IVec3)> = HashMap::new();
+
+    for (line, branch) in input.lines().enumerate().map(|(i, b)| (i + 1, b)) {
+        let mut current = IVec3::ZERO;
+        let mut own = HashSet::from([current]);
+
+        for (index, text) in branch.split(',').enumerate().map(|(i, t)| (i + 1, t)) {
+            let mut report = |kind| {
+                problems.push(Problem {
+                    line,
+                    index,
+                    text: text.to_string(),
+                    kind,
+                })
+            };
+
+            let m = match Move::from_str(text) {
+                Ok(m) => m,
+                Err(e) => {
+                    report(ProblemKind::Unparsable(e));
+                    continue;
+                }
+            };
+
+            if m.dist <= 0 {
+                report(ProblemKind::BadLength(m.dist));
+                continue;
+            }
+            if index == 1 && m.dir != IVec3::Z {
+                report(ProblemKind::OffTrunk);
+            }
+
+            // Each kind of problem is reported once per move, at the first segment it occurs.
+            let mut found = Vec::new();
+            for _ in 0..m.dist {
+                let previous = current;
+                current += m.dir;
+
+                let kind = if current.z < 0 {
+                    Some(ProblemKind::BelowGround(current))
+                } else if own.contains(&current) {
+                    Some(ProblemKind::Retraces(current))
+                } else {
+                    match occupied.get(&current) {
+                        Some(&(other, from)) if from != previous => Some(ProblemKind::Collides {
+                            at: current,
+                            line: other,
+                        }),
+                        _ => None,
+                    }
+                };
+
+                if let Some(kind) = kind
+                    && !found
+                        .iter()
+                        .any(|f| std::mem::discriminant(f) == std::mem::discriminant(&kind))
+                {
+                    found.push(kind);
+                }
+
+                own.insert(current);
+                occupied.entry(current).or_insert((line, previous));
+            }
+
+            found.into_iter().for_each(&mut report);
+        }
+    }
+
+    problems
+}
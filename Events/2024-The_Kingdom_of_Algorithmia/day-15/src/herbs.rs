@@ -0,0 +1,214 @@
+use ahash::AHashMap;
+use glam::IVec2;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+
+const OFFSETS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// The shortest round trip from the entrance, with the herbs in the order they are picked and
+/// every cell walked through, starting and ending at the entrance.
+pub struct Collection {
+    pub distance: usize,
+    pub stops: Vec<(IVec2, char)>,
+    pub path: Vec<IVec2>,
+}
+
+#[derive(Debug)]
+pub enum Unsolvable {
+    NoEntrance,
+    AmbiguousEntrance(Vec<IVec2>),
+    UnreachableHerbs(Vec<char>),
+}
+
+impl fmt::Display for Unsolvable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsolvable::NoEntrance => write!(f, "there is no opening on the top row"),
+            Unsolvable::AmbiguousEntrance(openings) => {
+                write!(f, "the top row has {} openings:", openings.len())?;
+                for opening in openings {
+                    write!(f, " {}", opening)?;
+                }
+                Ok(())
+            }
+            Unsolvable::UnreachableHerbs(types) => {
+                let types: String = types.iter().collect();
+                write!(f, "no herb of type {} can be reached", types)
+            }
+        }
+    }
+}
+
+/// The single opening on the top row of the maze.
+pub fn find_entrance(maze: &AHashMap<IVec2, char>) -> Result<IVec2, Unsolvable> {
+    let mut openings: Vec<IVec2> = maze.keys().filter(|pos| pos.y == 0).copied().collect();
+    openings.sort_by_key(|pos| pos.x);
+
+    match openings[..] {
+        [] => Err(Unsolvable::NoEntrance),
+        [entrance] => Ok(entrance),
+        _ => Err(Unsolvable::AmbiguousEntrance(openings)),
+    }
+}
+
+/// Steps from one cell to every reachable cell of the maze.
+fn distances_from(maze: &AHashMap<IVec2, char>, from: IVec2) -> AHashMap<IVec2, usize> {
+    let mut distances = AHashMap::from_iter([(from, 0)]);
+    let mut queue = VecDeque::from([(from, 0)]);
+
+    while let Some((current, dist)) = queue.pop_front() {
+        for offset in OFFSETS {
+            let neighbour = current + offset;
+            if maze.contains_key(&neighbour) && !distances.contains_key(&neighbour) {
+                distances.insert(neighbour, dist + 1);
+                queue.push_back((neighbour, dist + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+/// Cells walked from one cell to another, excluding the first.
+fn shortest_path(maze: &AHashMap<IVec2, char>, from: IVec2, to: IVec2) -> Vec<IVec2> {
+    let mut came_from = AHashMap::from_iter([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            break;
+        }
+        for offset in OFFSETS {
+            let neighbour = current + offset;
+            if maze.contains_key(&neighbour) && !came_from.contains_key(&neighbour) {
+                came_from.insert(neighbour, current);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut current = to;
+    while current != from {
+        path.push(current);
+        current = came_from[&current];
+    }
+    path.reverse();
+    path
+}
+
+/// Collects one herb of every type and returns to the entrance, in two phases. First the distances
+/// between the entrance and every herb are found with one BFS each, then Held-Karp picks which herb
+/// of each type to visit and in which order. The state is the set of types collected so far and
+/// the herb last picked, so the cost grows with the herbs rather than the size of the maze.
+pub fn collect_herbs(
+    maze: &AHashMap<IVec2, char>,
+    entrance: IVec2,
+) -> Result<Collection, Unsolvable> {
+    let types: Vec<char> = maze
+        .values()
+        .filter(|&&symbol| symbol != '.')
+        .copied()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    // The entrance is point 0, followed by every herb that can be reached.
+    let from_entrance = distances_from(maze, entrance);
+    let mut herbs: Vec<(IVec2, char)> = maze
+        .iter()
+        .filter(|(pos, symbol)| **symbol != '.' && from_entrance.contains_key(pos))
+        .map(|(pos, symbol)| (*pos, *symbol))
+        .collect();
+
+    let unreachable: Vec<char> = types
+        .iter()
+        .filter(|t| !herbs.iter().any(|(_, symbol)| symbol == *t))
+        .copied()
+        .collect();
+    if !unreachable.is_empty() {
+        return Err(Unsolvable::UnreachableHerbs(unreachable));
+    }
+
+    herbs.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+    let points: Vec<IVec2> = std::iter::once(entrance)
+        .chain(herbs.iter().map(|(pos, _)| *pos))
+        .collect();
+    let bits: Vec<usize> =
+        std::iter::once(0)
+            .chain(herbs.iter().map(|(_, symbol)| {
+                1 << types.binary_search(symbol).expect("Every herb has a type")
+            }))
+            .collect();
+
+    let distance: Vec<Vec<Option<usize>>> = points
+        .iter()
+        .map(|&from| {
+            let reachable = distances_from(maze, from);
+            points.iter().map(|to| reachable.get(to).copied()).collect()
+        })
+        .collect();
+
+    let full = (1 << types.len()) - 1;
+    let n = points.len();
+    let state = |mask: usize, point: usize| mask * n + point;
+
+    let mut best: Vec<Option<usize>> = vec![None; (full + 1) * n];
+    let mut previous: Vec<usize> = vec![0; (full + 1) * n];
+    best[state(0, 0)] = Some(0);
+
+    for mask in 0..full {
+        for from in 0..n {
+            let Some(so_far) = best[state(mask, from)] else {
+                continue;
+            };
+
+            for to in 1..n {
+                if mask & bits[to] != 0 {
+                    continue;
+                }
+                let Some(step) = distance[from][to] else {
+                    continue;
+                };
+
+                let next = state(mask | bits[to], to);
+                if best[next].is_none_or(|b| so_far + step < b) {
+                    best[next] = Some(so_far + step);
+                    previous[next] = from;
+                }
+            }
+        }
+    }
+
+    let (distance, last) = (0..n)
+        .filter_map(|point| {
+            let there = best[state(full, point)]?;
+            Some((there + distance[point][0]?, point))
+        })
+        .min()
+        .expect("Every herb is reachable, so there is a route");
+
+    let mut stops = Vec::new();
+    let (mut mask, mut point) = (full, last);
+    while point != 0 {
+        stops.push(herbs[point - 1]);
+        let from = previous[state(mask, point)];
+        mask &= !bits[point];
+        point = from;
+    }
+    stops.reverse();
+
+    let mut path = vec![entrance];
+    let mut at = entrance;
+    for &(pos, _) in stops.iter().chain(std::iter::once(&(entrance, '.'))) {
+        path.extend(shortest_path(maze, at, pos));
+        at = pos;
+    }
+
+    Ok(Collection {
+        distance,
+        stops,
+        path,
+    })
+}
@@ -1,86 +1,105 @@
-use ahash::{AHashMap, AHashSet};
+mod herbs;
+
+use ahash::AHashMap;
 use glam::IVec2;
-use itertools::Itertools;
-use std::collections::VecDeque;
+use herbs::{Collection, Unsolvable, collect_herbs, find_entrance};
 
 fn main() {
     part_one();
     part_two();
     part_three();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let ["route", part] = args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        show_route(part.parse().expect("Part should be 1, 2 or 3"));
+    }
 }
 
 fn part_one() {
     let input = include_str!("../input1.txt");
     let maze = parse_input(input);
-    if let Some(distance) = find_distance(&maze) {
-        println!("Part 1. Distance: {}", distance);
-    } else {
-        println!("Part 1. Could not find a solution...");
+    match find_route(&maze) {
+        Ok(collection) => println!("Part 1. Distance: {}", collection.distance),
+        Err(reason) => println!("Part 1. Could not find a solution: {}", reason),
     }
 }
 
 fn part_two() {
     let input = include_str!("../input2.txt");
     let maze = parse_input(input);
-    if let Some(distance) = find_distance(&maze) {
-        println!("Part 2. Distance: {}", distance);
-    } else {
-        println!("Part 2. Could not find a solution...");
+    match find_route(&maze) {
+        Ok(collection) => println!("Part 2. Distance: {}", collection.distance),
+        Err(reason) => println!("Part 2. Could not find a solution: {}", reason),
     }
 }
 
 fn part_three() {
     let input = include_str!("../input3.txt");
     let maze = parse_input(input);
-    if let Some(distance) = find_distance(&maze) {
-        println!("Part 3. Distance: {}", distance);
-    } else {
-        println!("Part 3. Could not find a solution...");
+    match find_route(&maze) {
+        Ok(collection) => println!("Part 3. Distance: {}", collection.distance),
+        Err(reason) => println!("Part 3. Could not find a solution: {}", reason),
     }
 }
 
-fn find_distance(maze: &AHashMap<IVec2, char>) -> Option<usize> {
-    let start_and_end: IVec2 = maze.keys().find(|&&pos| pos.y == 0).cloned().unwrap();
-
-    let unique_herbs: Vec<char> = maze
-        .values()
-        .filter(|v| v != &&'.')
-        .unique()
-        .cloned()
-        .collect();
-
-    let herb_score: usize = unique_herbs.into_iter().map(|h| 1 << h as u8 - b'A').sum();
-
-    let mut seen = AHashSet::new();
-    let mut queue: VecDeque<(IVec2, usize, usize)> = VecDeque::from([(start_and_end, 0, 0)]);
-
-    while let Some((current_pos, current_inventory, current_dist)) = queue.pop_front() {
-        if current_pos == start_and_end && current_inventory == herb_score {
-            return Some(current_dist);
+/// The herbs picked on the shortest route of a part, in order, and the route drawn on the map,
+/// for `day-15 route PART`.
+fn show_route(part: usize) {
+    let input = match part {
+        1 => include_str!("../input1.txt"),
+        2 => include_str!("../input2.txt"),
+        3 => include_str!("../input3.txt"),
+        _ => panic!("Part should be 1, 2 or 3"),
+    };
+
+    let collection = match find_route(&parse_input(input)) {
+        Ok(collection) => collection,
+        Err(reason) => {
+            println!("No route collects every herb: {}", reason);
+            return;
         }
+    };
+
+    let mut step = 0;
+    for (i, (pos, herb)) in collection.stops.iter().enumerate() {
+        step += collection.path[step..]
+            .iter()
+            .position(|p| p == pos)
+            .unwrap_or(0);
+        println!("{:>3}. {} at {} after {} steps", i + 1, herb, pos, step);
+    }
+    println!("Distance: {}", collection.distance);
+    println!("{}", render(input, &collection));
+}
 
-        for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
-            let neighbour_pos = current_pos + offset;
-
-            if let Some(&symbol) = maze.get(&neighbour_pos) {
-                let herb = match symbol {
-                    '.' => 0,
-                    _ => 1 << symbol as u8 - b'A',
-                };
-
-                let neighbour_inventory = current_inventory | herb;
+fn find_route(maze: &AHashMap<IVec2, char>) -> Result<Collection, Unsolvable> {
+    let entrance = find_entrance(maze)?;
+    collect_herbs(maze, entrance)
+}
 
-                if seen.contains(&(neighbour_pos, neighbour_inventory)) {
-                    continue;
-                }
+/// The map with the route walked marked by `*`, the entrance by `@` and the herbs picked in
+/// lowercase.
+fn render(input: &str, collection: &Collection) -> String {
+    let mut cells: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
 
-                seen.insert((neighbour_pos, neighbour_inventory));
-                queue.push_back((neighbour_pos, neighbour_inventory, current_dist + 1));
-            }
+    for pos in collection.path.iter() {
+        let cell = &mut cells[pos.y as usize][pos.x as usize];
+        if *cell == '.' {
+            *cell = '*';
         }
     }
+    for (pos, herb) in collection.stops.iter() {
+        cells[pos.y as usize][pos.x as usize] = herb.to_ascii_lowercase();
+    }
+    if let Some(entrance) = collection.path.first() {
+        cells[entrance.y as usize][entrance.x as usize] = '@';
+    }
 
-    None
+    cells
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn parse_input(input: &str) -> AHashMap<IVec2, char> {
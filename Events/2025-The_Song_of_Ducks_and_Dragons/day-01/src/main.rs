@@ -1,78 +1,80 @@
+mod ring;
+
+use ring::{Boundary, Instruction, Trajectory, run};
+
 fn main() {
     part_one();
     part_two();
     part_three();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let ["run", path, policy @ ..] = &args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        let input = std::fs::read_to_string(path).expect("Program should be readable");
+        let boundary = policy
+            .first()
+            .map_or(Ok(Boundary::Wrap), |p| p.parse())
+            .unwrap_or_else(|e| panic!("{}", e));
+        trace(&input, boundary);
+    }
 }
 
 fn part_one() {
     let input = include_str!("../input1.txt");
-    let (names, moves) = parse(input);
-
-    let length = names.len() as i32;
-    let mut current = 0;
-
-    for Step { delta } in moves.iter() {
-        current = (current + delta).clamp(0, length - 1);
-    }
-
-    let name = names[current as usize];
-    println!("Part 1. Name = {}", name);
+    let trajectory = select(input, Boundary::Clamp);
+    println!("Part 1. Name = {}", trajectory.selected());
 }
 
 fn part_two() {
     let input = include_str!("../input2.txt");
-    let (names, moves) = parse(input);
-
-    let length = names.len() as i32;
-    let mut current = 0;
-
-    for Step { delta } in moves.iter() {
-        current = (current + delta).rem_euclid(length);
-    }
-
-    let name = names[current as usize];
-    println!("Part 2. Name = {}", name);
+    let trajectory = select(input, Boundary::Wrap);
+    println!("Part 2. Name = {}", trajectory.selected());
 }
 
 fn part_three() {
     let input = include_str!("../input3.txt");
-    let (mut names, moves) = parse(input);
-
-    let length = names.len() as i32;
-
-    for Step { delta } in moves.iter() {
-        let swap_index = delta.rem_euclid(length) as usize;
-        names.swap(0, swap_index);
-    }
+    let trajectory = select(input, Boundary::Swap);
+    println!("Part 3. Name = {}", trajectory.selected());
+}
 
-    let name = names[0];
-    println!("Part 3. Name = {}", name);
+fn select(input: &str, boundary: Boundary) -> Trajectory<'_> {
+    parse(input)
+        .and_then(|(names, program)| run(&names, &program, boundary))
+        .expect("Program should run")
 }
 
-struct Step {
-    delta: i32,
+/// Every step of a program from a file, for `day-01 run FILE [POLICY]`. The file holds the names
+/// and the program as in the puzzle input, and the policy defaults to `wrap`.
+fn trace(input: &str, boundary: Boundary) {
+    match parse(input).and_then(|(names, program)| run(&names, &program, boundary)) {
+        Ok(trajectory) => {
+            for (i, frame) in trajectory.frames.iter().enumerate() {
+                println!(
+                    "{:>4}. {:<12} -> {:>3} {}",
+                    i + 1,
+                    frame.instruction.to_string(),
+                    frame.cursor,
+                    frame.name
+                );
+            }
+            println!("Names: {}", trajectory.names.join(","));
+            println!("Name = {}", trajectory.selected());
+        }
+        Err(e) => println!("{}", e),
+    }
 }
 
-fn parse(s: &str) -> (Vec<&str>, Vec<Step>) {
-    let (names, moves) = s.trim().split_once("\n\n").unwrap();
+fn parse(s: &str) -> Result<(Vec<&str>, Vec<Instruction<'_>>), String> {
+    let (names, program) = s
+        .trim()
+        .split_once("\n\n")
+        .ok_or("Expected the names and the program separated by a blank line")?;
 
     let names: Vec<&str> = names.split(',').collect();
 
-    let moves: Vec<Step> = moves
+    let program = program
         .split(',')
-        .map(|m| {
-            let (dir, count) = m.split_at(1);
-            let count = count.parse::<i32>().unwrap();
-
-            let delta = match dir {
-                "L" => -1 * count,
-                "R" => count,
-                _ => panic!("Bad input!"),
-            };
-
-            Step { delta }
-        })
-        .collect();
+        .map(|i| Instruction::parse(i.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    (names, moves)
+    Ok((names, program))
 }
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// How a move is applied when it would run past either end of the list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Boundary {
+    /// Stop at the first or last name.
+    Clamp,
+    /// Continue from the other end.
+    Wrap,
+    /// Bounce back off the end.
+    Reflect,
+    /// Stay put and swap the current name with the one the move lands on, wrapping.
+    Swap,
+    /// Stay put and rotate the list underneath, so the name the move lands on comes to the cursor.
+    Rotate,
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(Boundary::Clamp),
+            "wrap" => Ok(Boundary::Wrap),
+            "reflect" => Ok(Boundary::Reflect),
+            "swap" => Ok(Boundary::Swap),
+            "rotate" => Ok(Boundary::Rotate),
+            _ => Err(format!("Bad boundary policy: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Boundary::Clamp => "clamp",
+            Boundary::Wrap => "wrap",
+            Boundary::Reflect => "reflect",
+            Boundary::Swap => "swap",
+            Boundary::Rotate => "rotate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One step of a program walking a list of names. Programs separate instructions by commas:
+///
+/// - `L<n>` and `R<n>` move `n` names left or right, under the current boundary policy
+/// - `@<name>` jumps to a name
+/// - `~<n>` reverses the `n` names starting at the cursor, wrapping around the end of the list
+/// - `!<policy>` switches the boundary policy to `clamp`, `wrap`, `reflect`, `swap` or `rotate`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Instruction<'a> {
+    Move(i64),
+    JumpTo(&'a str),
+    Reverse(usize),
+    Policy(Boundary),
+}
+
+impl<'a> Instruction<'a> {
+    pub fn parse(s: &'a str) -> Result<Self, String> {
+        let count = |n: &str| {
+            n.parse::<i32>()
+                .map_err(|e| format!("Bad count in {}: {}", s, e))
+        };
+
+        match s.split_at_checked(1) {
+            Some(("L", n)) => Ok(Instruction::Move(-i64::from(count(n)?))),
+            Some(("R", n)) => Ok(Instruction::Move(i64::from(count(n)?))),
+            Some(("@", name)) => Ok(Instruction::JumpTo(name)),
+            Some(("~", n)) => match count(n)? {
+                n if n >= 0 => Ok(Instruction::Reverse(n as usize)),
+                _ => Err(format!("Cannot reverse a negative number of names: {}", s)),
+            },
+            Some(("!", policy)) => Ok(Instruction::Policy(policy.parse()?)),
+            _ => Err(format!("Bad instruction: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Instruction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Move(delta) if *delta < 0 => write!(f, "L{}", -delta),
+            Instruction::Move(delta) => write!(f, "R{}", delta),
+            Instruction::JumpTo(name) => write!(f, "@{}", name),
+            Instruction::Reverse(count) => write!(f, "~{}", count),
+            Instruction::Policy(policy) => write!(f, "!{}", policy),
+        }
+    }
+}
+
+/// The cursor and the name under it after an instruction.
+#[derive(Debug)]
+pub struct Frame<'a> {
+    pub instruction: Instruction<'a>,
+    pub cursor: usize,
+    pub name: &'a str,
+}
+
+pub struct Trajectory<'a> {
+    pub frames: Vec<Frame<'a>>,
+    pub names: Vec<&'a str>,
+    pub cursor: usize,
+}
+
+impl<'a> Trajectory<'a> {
+    pub fn selected(&self) -> &'a str {
+        self.names[self.cursor]
+    }
+}
+
+/// Runs a program over the names from the first one, starting with the given policy.
+pub fn run<'a>(
+    names: &[&'a str],
+    program: &[Instruction<'a>],
+    boundary: Boundary,
+) -> Result<Trajectory<'a>, String> {
+    if names.is_empty() {
+        return Err("There are no names to choose from".to_string());
+    }
+
+    let mut names = names.to_vec();
+    let mut cursor = 0;
+    let mut boundary = boundary;
+    let mut frames = Vec::with_capacity(program.len());
+    let length = names.len() as i64;
+
+    for &instruction in program {
+        match instruction {
+            Instruction::Move(delta) => {
+                let target = cursor as i64 + delta;
+                match boundary {
+                    Boundary::Clamp => cursor = target.clamp(0, length - 1) as usize,
+                    Boundary::Wrap => cursor = target.rem_euclid(length) as usize,
+                    Boundary::Reflect => {
+                        let period = (2 * (length - 1)).max(1);
+                        let folded = target.rem_euclid(period);
+                        cursor = folded.min(period - folded) as usize;
+                    }
+                    Boundary::Swap => names.swap(cursor, target.rem_euclid(length) as usize),
+                    Boundary::Rotate => names.rotate_left(delta.rem_euclid(length) as usize),
+                }
+            }
+            Instruction::JumpTo(name) => {
+                cursor = names
+                    .iter()
+                    .position(|n| *n == name)
+                    .ok_or_else(|| format!("Cannot jump to unknown name {}", name))?;
+            }
+            Instruction::Reverse(count) => {
+                let count = count.min(names.len());
+                let indices: Vec<usize> = (0..count).map(|i| (cursor + i) % names.len()).collect();
+                for i in 0..count / 2 {
+                    names.swap(indices[i], indices[count - 1 - i]);
+                }
+            }
+            Instruction::Policy(policy) => boundary = policy,
+        }
+
+        frames.push(Frame {
+            instruction,
+            cursor,
+            name: names[cursor],
+        });
+    }
+
+    Ok(Trajectory {
+        frames,
+        names,
+        cursor,
+    })
+}